@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use html_escape::{encode_double_quoted_attribute, encode_text};
+use yew::virtual_dom::VNode;
+use yew::{Callback, Html};
+
+use crate::filter::Filters;
+use crate::live::LiveState;
+use crate::params::SearchParams;
+use crate::pushshift::{format_timestamp, CardContext, RedditType};
+use crate::settings::SensitiveMedia;
+
+/// Embedded so the archive looks like the app without any other files
+static STYLE: &str = include_str!("../static/style.css");
+
+/// Hide controls that need the app to work
+static ARCHIVE_STYLE: &str = "
+.archive .show_more { display: none; }
+.archive_meta { margin: auto; max-width: 64rem; }
+.archive_meta code { word-break: break-all; }
+";
+
+/// Everything that goes into an archive page
+pub struct Archive {
+    pub params: SearchParams,
+    pub filters: Filters,
+    /// Query URLs fetched for the results, with unix time of each request
+    pub queries: Vec<(String, i64)>,
+    pub items: Vec<Rc<RedditType>>,
+    pub live: Rc<HashMap<String, Rc<LiveState>>>,
+}
+
+impl Archive {
+    /// Name of the downloaded file
+    pub fn filename(&self) -> String {
+        let now = (js_sys::Date::now() / 1000.0) as i64;
+        let time = format_timestamp(now, 0).replace([' ', ':'], "-");
+        format!("results-{}.html", time)
+    }
+
+    /// Render a standalone HTML page with the result cards
+    pub fn render(&self) -> String {
+        let mut cards = String::new();
+        for card in self.cards() {
+            write_html(&card, &mut cards);
+        }
+        let summary = encode_text(&self.params.summary()).into_owned();
+        let now = (js_sys::Date::now() / 1000.0) as i64;
+
+        let queries: String = self
+            .queries
+            .iter()
+            .map(|(url, time)| {
+                format!(
+                    "<li><code>{}</code> requested {} UTC</li>",
+                    encode_text(url),
+                    format_timestamp(*time, 0)
+                )
+            })
+            .collect();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8" />
+<meta name="viewport" content="width=device-width, initial-scale=1.0">
+<title>rGrep archive: {summary}</title>
+<style>{style}{archive_style}</style>
+</head>
+<body class="archive">
+<div class="archive_meta">
+<h1>{summary}</h1>
+<p>{count} results, removed content: {removed}. Saved {now} UTC.</p>
+<p>Queries:</p>
+<ol>{queries}</ol>
+</div>
+<div class="results">{cards}</div>
+</body>
+</html>
+"#,
+            summary = summary,
+            style = STYLE,
+            archive_style = ARCHIVE_STYLE,
+            count = self.items.len(),
+            removed = encode_text(&self.filters.removed.to_string()),
+            now = format_timestamp(now, 0),
+            queries = queries,
+            cards = cards,
+        )
+    }
+
+    /// Result cards with long bodies and sensitive media shown in full,
+    /// since the archive has no script to reveal them on click
+    fn cards(&self) -> Vec<Html> {
+        let on_expand = Callback::noop();
+        self.items
+            .iter()
+            .map(|r| {
+                let live = self.live.get(&r.fullname());
+                r.html(&CardContext {
+                    live: live.map(Rc::as_ref),
+                    on_expand: &on_expand,
+                    sensitive_media: SensitiveMedia::Show,
+                    revealed: true,
+                    on_reveal: Callback::noop(),
+                    expanded: true,
+                    on_toggle_expanded: Callback::noop(),
+                    new: false,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Elements that have no closing tag
+static VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Write out the markup of elements and text, listeners are left out
+fn write_html(node: &VNode, out: &mut String) {
+    match node {
+        VNode::VTag(tag) => {
+            let name = tag.tag();
+            out.push('<');
+            out.push_str(name);
+            for (key, value) in tag.attributes.iter() {
+                out.push_str(&format!(
+                    " {}=\"{}\"",
+                    key,
+                    encode_double_quoted_attribute(value)
+                ));
+            }
+            if let Some(value) = tag.value() {
+                out.push_str(&format!(
+                    " value=\"{}\"",
+                    encode_double_quoted_attribute(value.as_ref())
+                ));
+            }
+            out.push('>');
+            if VOID_ELEMENTS.contains(&name) {
+                return;
+            }
+            for child in tag.children().iter() {
+                write_html(child, out);
+            }
+            out.push_str(&format!("</{}>", name));
+        }
+        VNode::VText(text) => out.push_str(&encode_text(text.text.as_ref())),
+        VNode::VList(list) => {
+            for child in list.iter() {
+                write_html(child, out);
+            }
+        }
+        // Cards are plain markup, nothing else is expected
+        VNode::VComp(_) | VNode::VPortal(_) | VNode::VRef(_) => {
+            log::warn!("Unable to render {:?} into an archive", node);
+        }
+    }
+}
@@ -0,0 +1,7 @@
+use gloo_worker::Registrable;
+use pushshift_web::worker::ParseWorker;
+
+fn main() {
+    wasm_logger::init(wasm_logger::Config::default());
+    ParseWorker::registrar().register();
+}
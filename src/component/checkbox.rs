@@ -0,0 +1,33 @@
+use web_sys::Event;
+use yew::prelude::*;
+
+use super::checked_value;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub id: String,
+    pub label: String,
+    pub checked: bool,
+    pub on_change: Callback<bool>,
+}
+
+#[function_component(Checkbox)]
+pub fn checkbox(props: &Props) -> Html {
+    let Props {
+        id,
+        label,
+        checked,
+        on_change,
+    } = props.clone();
+
+    let onchange = Callback::from(move |event: Event| {
+        on_change.emit(checked_value(event));
+    });
+
+    html! {
+        <div class="checkbox">
+            <input type="checkbox" id={id.clone()} {checked} {onchange} />
+            <label for={id}>{label}</label>
+        </div>
+    }
+}
@@ -0,0 +1,117 @@
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+use super::checkbox::{self, Checkbox};
+use super::text_input::{self, TextInput};
+use crate::corpus::{Purge, Stats};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    /// Whether fetched results are kept in the corpus
+    pub enabled: bool,
+    /// Whether searches run against the corpus instead of the API
+    pub only: bool,
+    pub stats: Option<Stats>,
+    pub status: Option<String>,
+    pub on_enabled_change: Callback<bool>,
+    pub on_only_change: Callback<bool>,
+    pub on_refresh: Callback<()>,
+    pub on_purge: Callback<Purge>,
+}
+
+/// Settings of the local corpus and removal of what it holds
+#[function_component(CorpusPanel)]
+pub fn corpus_panel(props: &Props) -> Html {
+    let Props {
+        enabled,
+        only,
+        stats,
+        status,
+        on_enabled_change,
+        on_only_change,
+        on_refresh,
+        on_purge,
+    } = props.clone();
+
+    // Subreddit or author to remove
+    let target = use_state(String::new);
+
+    let manage = if enabled {
+        let size = match stats {
+            Some(Stats {
+                items,
+                bytes: Some(bytes),
+            }) => format!(
+                "{} items, {:.1} MB stored by this site",
+                items,
+                bytes as f64 / (1024.0 * 1024.0)
+            ),
+            Some(stats) => format!("{} items", stats.items),
+            None => "Size not checked".to_owned(),
+        };
+        let on_refresh = Callback::from(move |_: MouseEvent| on_refresh.emit(()));
+        let button = |text: &'static str, purge: fn(String) -> Purge| {
+            let target = (*target).clone();
+            let on_purge = on_purge.clone();
+            let onclick = Callback::from(move |_: MouseEvent| on_purge.emit(purge(target.clone())));
+            html! {
+                <button type="button" class="history_button" {onclick}>{text}</button>
+            }
+        };
+        let only = checkbox::Props {
+            id: "corpus_only".to_owned(),
+            label: "Search the local corpus instead of the API".to_owned(),
+            checked: only,
+            on_change: on_only_change,
+        };
+        let target_input = text_input::Props {
+            id: "purge_target".to_owned(),
+            value: (*target).clone(),
+            on_change: {
+                let target = target.clone();
+                Callback::from(move |s| target.set(s))
+            },
+        };
+        html! {
+            <>
+                <Checkbox ..only />
+                <div class="preset_actions">
+                    <span class="preset_status">{size}</span>
+                    <button type="button" class="history_button" onclick={on_refresh}>
+                        {"Refresh"}
+                    </button>
+                </div>
+                <div class="search_full">
+                    <label for="purge_target">{"Subreddit or author:"}</label>
+                    <TextInput ..target_input />
+                </div>
+                <div class="preset_actions">
+                    {button("Remove subreddit", Purge::Subreddit)}
+                    {button("Remove author", Purge::Author)}
+                    {button("Remove everything", |_| Purge::All)}
+                </div>
+            </>
+        }
+    } else {
+        html! {}
+    };
+    let status = match status {
+        Some(s) => html! { <div class="preset_status">{s}</div> },
+        None => html! {},
+    };
+    let enabled = checkbox::Props {
+        id: "corpus".to_owned(),
+        label: "Keep fetched results in a local corpus in this browser".to_owned(),
+        checked: enabled,
+        on_change: on_enabled_change,
+    };
+
+    html! {
+        <details class="settings">
+            <summary>{"Local corpus"}</summary>
+            <Checkbox ..enabled />
+            {manage}
+            {status}
+        </details>
+    }
+}
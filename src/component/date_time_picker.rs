@@ -0,0 +1,280 @@
+use time::util::days_in_year_month;
+use time::{Date, Duration, PrimitiveDateTime, Time};
+use web_sys::{Event, HtmlElement, InputEvent, KeyboardEvent, MouseEvent};
+use yew::prelude::*;
+
+use super::{changed_value, input_value, Width};
+use crate::params::{format_local, now_local, parse_local};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub width: Width,
+    pub id: String,
+    pub label: String,
+    pub value: String,
+    /// Start of the range this picks the end of, earlier days can't be picked
+    #[prop_or_default]
+    pub min: String,
+    /// End of the range this picks the start of, later days can't be picked
+    #[prop_or_default]
+    pub max: String,
+    pub tz_offset: i64,
+    pub on_change: Callback<String>,
+}
+
+static WEEKDAYS: [&str; 7] = ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"];
+
+/// Text input for a local time, with a calendar that can be used with the keyboard
+///
+/// Arrow keys move between days, page up and down between months, enter picks
+/// the day and escape closes the calendar.
+#[function_component(DateTimePicker)]
+pub fn date_time_picker(props: &Props) -> Html {
+    let Props {
+        width,
+        id,
+        label,
+        value,
+        min,
+        max,
+        tz_offset,
+        on_change,
+    } = props.clone();
+
+    // Day with keyboard focus, its month is shown, none while closed
+    let focused = use_state(|| None::<Date>);
+    let days_ref = use_node_ref();
+    {
+        let days_ref = days_ref.clone();
+        use_effect_with_deps(
+            move |open| {
+                if let (true, Some(days)) = (*open, days_ref.cast::<HtmlElement>()) {
+                    let _ = days.focus();
+                }
+                || ()
+            },
+            focused.is_some(),
+        );
+    }
+
+    let selected = parse_local(&value);
+    let min = parse_local(&min).map(|m| m.date());
+    let max = parse_local(&max).map(|m| m.date());
+    let allowed = move |day: Date| min.is_none_or(|m| day >= m) && max.is_none_or(|m| day <= m);
+    let today = now_local(tz_offset).date();
+    let start = selected.map(|s| s.date()).unwrap_or(today);
+    let time = selected.map(|s| s.time()).unwrap_or(Time::MIDNIGHT);
+
+    let emit = {
+        let on_change = on_change.clone();
+        Callback::from(move |t: PrimitiveDateTime| on_change.emit(format_local(t)))
+    };
+    let pick = {
+        let emit = emit.clone();
+        Callback::from(move |day: Date| emit.emit(day.with_time(time)))
+    };
+
+    let oninput = {
+        let on_change = on_change.clone();
+        Callback::from(move |e: InputEvent| on_change.emit(input_value(e)))
+    };
+    let on_input_key = {
+        let focused = focused.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "ArrowDown" => {
+                e.prevent_default();
+                focused.set(Some(start));
+            }
+            "Escape" => focused.set(None),
+            _ => {}
+        })
+    };
+    let toggle = {
+        let focused = focused.clone();
+        Callback::from(move |_: MouseEvent| {
+            focused.set(match *focused {
+                Some(_) => None,
+                None => Some(start),
+            })
+        })
+    };
+
+    let input = html! {
+        <div class="date_picker_input">
+            <input type="text" id={id.clone()} {value} {oninput} onkeydown={on_input_key}
+                placeholder="YYYY-MM-DD HH:MM" autocomplete="off" />
+            <button type="button" class="date_picker_toggle" title="Pick a date" onclick={toggle}>
+                {"▾"}
+            </button>
+        </div>
+    };
+
+    let shown = match *focused {
+        Some(day) => day,
+        None => {
+            return html! {
+                <div class={classes!(width.class(), "date_picker")}>
+                    <div>
+                        <label for={id}>{label}</label>
+                        {input}
+                    </div>
+                </div>
+            }
+        }
+    };
+
+    let onkeydown = {
+        let focused = focused.clone();
+        let pick = pick.clone();
+        Callback::from(move |e: KeyboardEvent| {
+            let moved = match e.key().as_str() {
+                "ArrowLeft" => shown.previous_day(),
+                "ArrowRight" => shown.next_day(),
+                "ArrowUp" => shown.checked_sub(Duration::weeks(1)),
+                "ArrowDown" => shown.checked_add(Duration::weeks(1)),
+                "PageUp" => Some(add_months(shown, -1)),
+                "PageDown" => Some(add_months(shown, 1)),
+                "Home" => shown.replace_day(1).ok(),
+                "End" => shown
+                    .replace_day(days_in_year_month(shown.year(), shown.month()))
+                    .ok(),
+                "Enter" | " " => {
+                    if allowed(shown) {
+                        pick.emit(shown);
+                    }
+                    None
+                }
+                "Escape" => {
+                    focused.set(None);
+                    None
+                }
+                _ => return,
+            };
+            e.prevent_default();
+            if let Some(day) = moved {
+                focused.set(Some(day));
+            }
+        })
+    };
+
+    let month_button = |text: &'static str, months: i32| {
+        let focused = focused.clone();
+        let onclick =
+            Callback::from(move |_: MouseEvent| focused.set(Some(add_months(shown, months))));
+        html! {
+            <button type="button" class="date_picker_nav" tabindex="-1" {onclick}>{text}</button>
+        }
+    };
+
+    // Six weeks from the monday before the first of the month
+    let first = shown.replace_day(1).unwrap_or(shown);
+    let grid_start = first.saturating_sub(Duration::days(
+        first.weekday().number_days_from_monday() as i64,
+    ));
+    let range = match (
+        min.or(selected.map(|s| s.date())),
+        max.or(selected.map(|s| s.date())),
+    ) {
+        (Some(from), Some(to)) if min.is_some() || max.is_some() => Some((from, to)),
+        _ => None,
+    };
+    let days = (0..42).map(|i| {
+        let day = grid_start.saturating_add(Duration::days(i));
+        let class = classes!(
+            "date_picker_day",
+            (day.month() != shown.month()).then_some("other_month"),
+            (day == today).then_some("today"),
+            range
+                .filter(|(from, to)| *from <= day && day <= *to)
+                .map(|_| "in_range"),
+            (Some(day) == selected.map(|s| s.date())).then_some("selected"),
+            (day == shown).then_some("focused"),
+        );
+        let onclick = {
+            let focused = focused.clone();
+            let pick = pick.clone();
+            Callback::from(move |_: MouseEvent| {
+                focused.set(Some(day));
+                pick.emit(day);
+            })
+        };
+        html! {
+            <button type="button" {class} tabindex="-1" disabled={!allowed(day)} {onclick}>
+                {day.day()}
+            </button>
+        }
+    });
+
+    let time_input =
+        |name: &'static str, value: u8, limit: u8, set: fn(Time, u8) -> Option<Time>| {
+            let emit = emit.clone();
+            let day = selected.map(|s| s.date()).unwrap_or(shown);
+            // Committed once the field is left, so typing isn't reformatted midway
+            let onchange = Callback::from(move |e: Event| {
+                let new_time = changed_value(e)
+                    .parse()
+                    .ok()
+                    .filter(|v| *v <= limit)
+                    .and_then(|v| set(time, v));
+                if let Some(t) = new_time {
+                    emit.emit(day.with_time(t));
+                }
+            });
+            html! {
+                <input type="number" aria-label={name} min="0" max={limit.to_string()}
+                    value={format!("{:02}", value)} {onchange} />
+            }
+        };
+
+    let clear = {
+        let focused = focused.clone();
+        let on_change = on_change.clone();
+        Callback::from(move |_: MouseEvent| {
+            on_change.emit(String::new());
+            focused.set(None);
+        })
+    };
+    let close = {
+        let focused = focused.clone();
+        Callback::from(move |_: MouseEvent| focused.set(None))
+    };
+
+    html! {
+        <div class={classes!(width.class(), "date_picker")}>
+            <div>
+                <label for={id}>{label}</label>
+                {input}
+            </div>
+            <div class="date_picker_popup">
+                <div class="date_picker_header">
+                    {month_button("‹", -1)}
+                    <span>{format!("{} {}", shown.month(), shown.year())}</span>
+                    {month_button("›", 1)}
+                </div>
+                <div class="date_picker_days" tabindex="0" ref={days_ref} {onkeydown}>
+                    { for WEEKDAYS.iter().map(|d| html! { <span class="date_picker_weekday">{d}</span> }) }
+                    { for days }
+                </div>
+                <div class="date_picker_time">
+                    {time_input("Hour", time.hour(), 23, |t, h| Time::from_hms(h, t.minute(), 0).ok())}
+                    {":"}
+                    {time_input("Minute", time.minute(), 59, |t, m| Time::from_hms(t.hour(), m, 0).ok())}
+                    <button type="button" class="history_button" onclick={clear}>{"Clear"}</button>
+                    <button type="button" class="history_button" onclick={close}>{"Done"}</button>
+                </div>
+            </div>
+        </div>
+    }
+}
+
+/// The same day `months` later, or the last day of that month if it is shorter
+fn add_months(day: Date, months: i32) -> Date {
+    let index = day.year() * 12 + day.month() as i32 - 1 + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u8 + 1);
+    let month = match time::Month::try_from(month) {
+        Ok(m) => m,
+        Err(_) => return day,
+    };
+    let last = days_in_year_month(year, month);
+    Date::from_calendar_date(year, month, day.day().min(last)).unwrap_or(day)
+}
@@ -0,0 +1,83 @@
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+use crate::diagnostics::RequestLog;
+use crate::params::format_time;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub entries: Vec<RequestLog>,
+    pub tz_offset: i64,
+    pub on_copy: Callback<u32>,
+    pub on_open: Callback<u32>,
+}
+
+#[function_component(DiagnosticsPanel)]
+pub fn diagnostics_panel(props: &Props) -> Html {
+    let Props {
+        entries,
+        tz_offset,
+        on_copy,
+        on_open,
+    } = props.clone();
+
+    if entries.is_empty() {
+        return html! {};
+    }
+
+    let button = |text: &'static str, callback: &Callback<u32>, id: u32, disabled: bool| {
+        let callback = callback.clone();
+        let onclick = Callback::from(move |_: MouseEvent| callback.emit(id));
+        html! {
+            <button class="history_button" {disabled} {onclick}>{text}</button>
+        }
+    };
+
+    let rows = entries.iter().map(|e| {
+        let mut details = vec![
+            format_time(e.time, tz_offset).unwrap_or_default(),
+            e.backend.clone(),
+        ];
+        match (e.status, e.latency_ms) {
+            (Some(status), Some(ms)) => details.push(format!("HTTP {} in {:.0} ms", status, ms)),
+            (None, Some(ms)) => details.push(format!("no response after {:.0} ms", ms)),
+            _ => details.push("pending".to_owned()),
+        }
+        if let Some(bytes) = e.bytes {
+            details.push(format!("{:.1} kB", bytes as f64 / 1000.0));
+        }
+        if let Some(records) = e.records {
+            details.push(format!("{} records", records));
+        }
+        if e.attempt > 0 {
+            details.push(format!("retry {}", e.attempt));
+        }
+
+        let problems = e
+            .error
+            .iter()
+            .chain(e.warnings.iter())
+            .map(|p| html! { <div class="history_details error">{p}</div> });
+
+        html! {
+            <li key={e.id.to_string()} class="history_entry">
+                <div class="history_title"><code>{&e.url}</code></div>
+                <div class="history_details">{details.join(" · ")}</div>
+                {for problems}
+                <div class="history_actions">
+                    {button("Copy as curl", &on_copy, e.id, false)}
+                    {button("Open raw JSON", &on_open, e.id, !e.has_body)}
+                </div>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="history">
+            <summary>{format!("Diagnostics ({} requests)", entries.len())}</summary>
+            <ul>
+                {for rows}
+            </ul>
+        </details>
+    }
+}
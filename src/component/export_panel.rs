@@ -0,0 +1,82 @@
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+use super::checkbox::{self, Checkbox};
+use super::select::{self, Select};
+use super::Width;
+use crate::export::{Column, ExportFormat, ExportOptions};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub options: ExportOptions,
+    pub error: Option<String>,
+    pub on_format_change: Callback<String>,
+    pub on_column_change: Callback<(Column, bool)>,
+    pub on_download: Callback<()>,
+    pub on_download_archive: Callback<()>,
+}
+
+/// Download of the shown results as a file or an HTML archive
+#[function_component(ExportPanel)]
+pub fn export_panel(props: &Props) -> Html {
+    let Props {
+        options,
+        error,
+        on_format_change,
+        on_column_change,
+        on_download,
+        on_download_archive,
+    } = props.clone();
+
+    let columns = if options.format == ExportFormat::Csv {
+        let checkbox = |column: Column| {
+            let on_column_change = on_column_change.clone();
+            let props = checkbox::Props {
+                id: format!("export_{}", column.name()),
+                label: column.name().to_owned(),
+                checked: options.columns.contains(&column),
+                on_change: Callback::from(move |b| on_column_change.emit((column, b))),
+            };
+            html! { <Checkbox ..props /> }
+        };
+        html! {
+            <div class="export_columns">
+                {for Column::all().into_iter().map(checkbox)}
+            </div>
+        }
+    } else {
+        html! {}
+    };
+    let error = match error {
+        Some(e) => html! { <div class="error">{e}</div> },
+        None => html! {},
+    };
+    let format = select::Props {
+        width: Width::Full,
+        id: "export_format".to_owned(),
+        class: String::new(),
+        label: "Format:".to_owned(),
+        options: ExportFormat::list(),
+        selected: options.format.to_string(),
+        on_input: on_format_change,
+    };
+    let on_download = Callback::from(move |_: MouseEvent| on_download.emit(()));
+    let on_download_archive = Callback::from(move |_: MouseEvent| on_download_archive.emit(()));
+
+    html! {
+        <details class="settings export">
+            <summary>{"Export results"}</summary>
+            <Select ..format />
+            {columns}
+            <div class="preset_actions">
+                <button type="button" class="history_button" onclick={on_download}>
+                    {"Download"}
+                </button>
+                <button type="button" class="history_button" onclick={on_download_archive}>
+                    {"Download HTML archive"}
+                </button>
+            </div>
+            {error}
+        </details>
+    }
+}
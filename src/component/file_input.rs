@@ -0,0 +1,39 @@
+use wasm_bindgen::{JsCast, UnwrapThrowExt};
+use web_sys::{Event, File, HtmlInputElement};
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub id: String,
+    pub label: String,
+    /// Accepted file types, as in the `accept` attribute
+    pub accept: String,
+    pub on_change: Callback<File>,
+}
+
+/// Button that lets the user pick a file
+#[function_component(FileInput)]
+pub fn file_input(props: &Props) -> Html {
+    let Props {
+        id,
+        label,
+        accept,
+        on_change,
+    } = props.clone();
+
+    let onchange = Callback::from(move |event: Event| {
+        let target: HtmlInputElement = event.target().unwrap_throw().dyn_into().unwrap_throw();
+        if let Some(file) = target.files().and_then(|f| f.get(0)) {
+            on_change.emit(file);
+        }
+        // Allow picking the same file again
+        target.set_value("");
+    });
+
+    html! {
+        <label class="file_input" for={id.clone()}>
+            {label}
+            <input type="file" id={id} {accept} {onchange} />
+        </label>
+    }
+}
@@ -0,0 +1,122 @@
+use web_sys::{FocusEvent, MouseEvent};
+use yew::prelude::*;
+
+use super::text_input::{self, TextInput};
+use crate::history::{HistoryEntry, Outcome};
+use crate::params::format_time;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub entries: Vec<HistoryEntry>,
+    pub tz_offset: i64,
+    pub on_run: Callback<u64>,
+    pub on_toggle_pinned: Callback<u64>,
+    pub on_rename: Callback<(u64, String)>,
+    pub on_delete: Callback<u64>,
+}
+
+#[function_component(HistoryPanel)]
+pub fn history_panel(props: &Props) -> Html {
+    let Props {
+        entries,
+        tz_offset,
+        on_run,
+        on_toggle_pinned,
+        on_rename,
+        on_delete,
+    } = props.clone();
+
+    // Entry being renamed and its new name
+    let editing = use_state(|| None::<(u64, String)>);
+
+    if entries.is_empty() {
+        return html! {};
+    }
+
+    let button = |text: &'static str, callback: &Callback<u64>, id: u64| {
+        let callback = callback.clone();
+        let onclick = Callback::from(move |_: MouseEvent| callback.emit(id));
+        html! {
+            <button class="history_button" {onclick}>{text}</button>
+        }
+    };
+
+    let rows = entries.iter().map(|e| {
+        let id = e.id;
+        let title = match &*editing {
+            Some((edit_id, name)) if *edit_id == id => {
+                let on_change = {
+                    let editing = editing.clone();
+                    Callback::from(move |s| editing.set(Some((id, s))))
+                };
+                let onsubmit = {
+                    let editing = editing.clone();
+                    let on_rename = on_rename.clone();
+                    let name = name.clone();
+                    Callback::from(move |e: FocusEvent| {
+                        e.prevent_default();
+                        on_rename.emit((id, name.clone()));
+                        editing.set(None);
+                    })
+                };
+                let input = text_input::Props {
+                    id: format!("rename_{}", id),
+                    value: name.clone(),
+                    on_change,
+                };
+                html! {
+                    <form class="history_rename" {onsubmit}>
+                        <TextInput ..input />
+                        <input type="submit" value="Save" />
+                    </form>
+                }
+            }
+            _ => html! {
+                <div class="history_title">{e.title()}</div>
+            },
+        };
+
+        let rename = {
+            let editing = editing.clone();
+            let name = e.name.clone().unwrap_or_default();
+            let onclick = Callback::from(move |_: MouseEvent| editing.set(Some((id, name.clone()))));
+            html! {
+                <button class="history_button" {onclick}>{"Rename"}</button>
+            }
+        };
+
+        let outcome = match &e.outcome {
+            Outcome::Running => "not finished".to_owned(),
+            Outcome::Succeeded => format!("{} results", e.results),
+            Outcome::Failed(error) => format!("failed: {}", error),
+        };
+        let details = format!(
+            "{} · {} · {}",
+            format_time(e.time, tz_offset).unwrap_or_default(),
+            outcome,
+            e.backend
+        );
+
+        html! {
+            <li key={id.to_string()} class={classes!("history_entry", e.pinned.then_some("pinned"))}>
+                {title}
+                <div class="history_details">{details}</div>
+                <div class="history_actions">
+                    {button("Run", &on_run, id)}
+                    {button(if e.pinned { "Unpin" } else { "Pin" }, &on_toggle_pinned, id)}
+                    {rename}
+                    {button("Delete", &on_delete, id)}
+                </div>
+            </li>
+        }
+    });
+
+    html! {
+        <details class="history">
+            <summary>{format!("Search history ({})", entries.len())}</summary>
+            <ul>
+                {for rows}
+            </ul>
+        </details>
+    }
+}
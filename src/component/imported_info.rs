@@ -0,0 +1,69 @@
+use web_sys::MouseEvent;
+use yew::prelude::*;
+
+/// How far the search of a dump file got
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ScanStatus {
+    pub read: u64,
+    pub total: u64,
+    pub skipped: usize,
+    pub running: bool,
+    /// Why the scan stopped early
+    pub note: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    /// Name of the file the results are from
+    pub name: String,
+    pub results: usize,
+    /// Set when the file is a dump being searched
+    pub scan: Option<ScanStatus>,
+    pub on_cancel: Callback<()>,
+}
+
+/// Where results read from a file came from
+#[function_component(ImportedInfo)]
+pub fn imported_info(props: &Props) -> Html {
+    let Props {
+        name,
+        results,
+        scan,
+        on_cancel,
+    } = props.clone();
+
+    let scan = match scan {
+        Some(scan) => scan,
+        None => {
+            return html! {
+                <div class="info">{format!("{} results from {}", results, name)}</div>
+            }
+        }
+    };
+
+    let mut status = if scan.running {
+        let percent = 100.0 * scan.read as f64 / scan.total.max(1) as f64;
+        format!(
+            "Searching {}: {:.0}% read, {} matches",
+            name, percent, results
+        )
+    } else {
+        format!("{} matches in {}", results, name)
+    };
+    if let Some(note) = &scan.note {
+        status += &format!(", {}", note);
+    }
+    if scan.skipped > 0 {
+        status += &format!(", {} records could not be read", scan.skipped);
+    }
+    let cancel = if scan.running {
+        let onclick = Callback::from(move |_: MouseEvent| on_cancel.emit(()));
+        html! { <button type="button" class="history_button" {onclick}>{"Cancel"}</button> }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class="info">{status}{" "}{cancel}</div>
+    }
+}
@@ -0,0 +1,122 @@
+use web_sys::{HtmlElement, KeyboardEvent, MouseEvent};
+use yew::prelude::*;
+
+use crate::media::{Media, MediaKind};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub media: Vec<Media>,
+    pub index: usize,
+    pub on_close: Callback<()>,
+}
+
+#[function_component(Lightbox)]
+pub fn lightbox(props: &Props) -> Html {
+    let Props {
+        media,
+        index,
+        on_close,
+    } = props.clone();
+
+    let current = use_state(|| index.min(media.len().saturating_sub(1)));
+
+    // Focus the overlay so keyboard navigation works right away
+    let node = use_node_ref();
+    {
+        let node = node.clone();
+        use_effect_with_deps(
+            move |_| {
+                if let Some(e) = node.cast::<HtmlElement>() {
+                    let _ = e.focus();
+                }
+                || ()
+            },
+            (),
+        );
+    }
+
+    let len = media.len();
+    let m = match media.get(*current) {
+        Some(m) => m.clone(),
+        None => return html! {},
+    };
+
+    let step = {
+        let current = current.clone();
+        move |forward: bool| {
+            let next = if forward {
+                (*current + 1) % len
+            } else {
+                (*current + len - 1) % len
+            };
+            current.set(next);
+        }
+    };
+
+    let onclick = {
+        let on_close = on_close.clone();
+        Callback::from(move |_: MouseEvent| on_close.emit(()))
+    };
+    let onkeydown = {
+        let step = step.clone();
+        Callback::from(move |e: KeyboardEvent| match e.key().as_str() {
+            "Escape" => on_close.emit(()),
+            "ArrowLeft" => step(false),
+            "ArrowRight" => step(true),
+            _ => {}
+        })
+    };
+    let button = |forward: bool, text: &'static str| {
+        let step = step.clone();
+        let onclick = Callback::from(move |e: MouseEvent| {
+            e.stop_propagation();
+            step(forward);
+        });
+        html! {
+            <button class="lightbox_nav" {onclick}>{text}</button>
+        }
+    };
+    let stop = Callback::from(|e: MouseEvent| e.stop_propagation());
+
+    let content = match m.kind {
+        MediaKind::Image => html! {
+            <img class="lightbox_media" src={m.url.clone()} alt="Reddit media" onclick={stop.clone()} />
+        },
+        MediaKind::Video => html! {
+            <video class="lightbox_media"
+                src={m.url.clone()}
+                poster={m.preview.clone()}
+                controls=true
+                autoplay=true
+                loop=true
+                onclick={stop.clone()} />
+        },
+    };
+
+    let (prev, next, counter) = if len > 1 {
+        (
+            button(false, "‹"),
+            button(true, "›"),
+            html! { <div class="lightbox_counter">{format!("{}/{}", *current + 1, len)}</div> },
+        )
+    } else {
+        (html! {}, html! {}, html! {})
+    };
+
+    let caption = match &m.caption {
+        Some(c) => html! { <div class="lightbox_caption" onclick={stop}>{c.clone()}</div> },
+        None => html! {},
+    };
+
+    html! {
+        <div class="lightbox" tabindex="0" ref={node} {onclick} {onkeydown}>
+            {prev}
+            <div class="lightbox_content">
+                {content}
+                {caption}
+                {counter}
+            </div>
+            {next}
+        </div>
+    }
+}
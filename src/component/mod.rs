@@ -1,9 +1,23 @@
 use wasm_bindgen::{JsCast, UnwrapThrowExt};
 use web_sys::{Event, HtmlInputElement, HtmlSelectElement, InputEvent};
 
+pub mod checkbox;
+pub mod corpus_panel;
+pub mod date_time_picker;
+pub mod diagnostics_panel;
+pub mod export_panel;
+pub mod file_input;
+pub mod history_panel;
+pub mod imported_info;
+pub mod lightbox;
+pub mod presets_editor;
+pub mod result_card;
+pub mod result_list;
 pub mod search_box;
 pub mod search_button;
+pub mod search_info;
 pub mod select;
+pub mod sentinel;
 pub mod text_input;
 
 fn input_value(e: InputEvent) -> String {
@@ -13,6 +27,18 @@ fn input_value(e: InputEvent) -> String {
     target.value()
 }
 
+fn changed_value(e: Event) -> String {
+    let event_target = e.target().unwrap_throw();
+    let target: HtmlInputElement = event_target.dyn_into().unwrap_throw();
+    target.value()
+}
+
+fn checked_value(e: Event) -> bool {
+    let event_target = e.target().unwrap_throw();
+    let target: HtmlInputElement = event_target.dyn_into().unwrap_throw();
+    target.checked()
+}
+
 fn select_value(e: InputEvent) -> String {
     let event: Event = e.dyn_into().unwrap_throw();
     let event_target = event.target().unwrap_throw();
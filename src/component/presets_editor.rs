@@ -0,0 +1,78 @@
+use web_sys::{File, MouseEvent};
+use yew::prelude::*;
+
+use super::file_input::{self, FileInput};
+use super::text_input::{self, TextInput};
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub name: String,
+    /// Whether a saved search is selected that can be deleted
+    pub can_delete: bool,
+    pub status: Option<String>,
+    pub on_name_change: Callback<String>,
+    pub on_save: Callback<()>,
+    pub on_delete: Callback<()>,
+    pub on_export: Callback<()>,
+    pub on_import: Callback<File>,
+}
+
+/// Saving, deleting, exporting and importing saved searches
+#[function_component(PresetsEditor)]
+pub fn presets_editor(props: &Props) -> Html {
+    let Props {
+        name,
+        can_delete,
+        status,
+        on_name_change,
+        on_save,
+        on_delete,
+        on_export,
+        on_import,
+    } = props.clone();
+
+    let button = |text: &'static str, callback: Callback<()>| {
+        let onclick = Callback::from(move |_: MouseEvent| callback.emit(()));
+        html! {
+            <button type="button" class="history_button" {onclick}>{text}</button>
+        }
+    };
+    let delete = if can_delete {
+        button("Delete selected", on_delete)
+    } else {
+        html! {}
+    };
+    let status = match status {
+        Some(s) => html! { <div class="preset_status">{s}</div> },
+        None => html! {},
+    };
+
+    let name = text_input::Props {
+        id: "preset_name".to_owned(),
+        value: name,
+        on_change: on_name_change,
+    };
+    let import = file_input::Props {
+        id: "import_presets".to_owned(),
+        label: "Import".to_owned(),
+        accept: ".json,application/json".to_owned(),
+        on_change: on_import,
+    };
+
+    html! {
+        <details class="settings">
+            <summary>{"Manage saved searches"}</summary>
+            <div class="search_full">
+                <label for="preset_name">{"Name:"}</label>
+                <TextInput ..name />
+            </div>
+            <div class="preset_actions">
+                {button("Save current search", on_save)}
+                {delete}
+                {button("Export", on_export)}
+                <FileInput ..import />
+            </div>
+            {status}
+        </details>
+    }
+}
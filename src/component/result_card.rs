@@ -0,0 +1,57 @@
+use std::rc::Rc;
+
+use yew::prelude::*;
+
+use crate::live::LiveState;
+use crate::media::OnExpand;
+use crate::pushshift::{CardContext, RedditType};
+use crate::settings::SensitiveMedia;
+
+/// Props are cheap to compare so untouched cards are not re-rendered
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub item: Rc<RedditType>,
+    pub live: Option<Rc<LiveState>>,
+    pub sensitive_media: SensitiveMedia,
+    pub on_expand: OnExpand,
+    pub new: bool,
+    /// Kept by the list so it survives the card being unmounted
+    pub revealed: bool,
+    pub expanded: bool,
+    /// Called with the fullname of the item
+    pub on_reveal: Callback<String>,
+    pub on_toggle_expanded: Callback<String>,
+}
+
+#[function_component(ResultCard)]
+pub fn result_card(props: &Props) -> Html {
+    let Props {
+        item,
+        live,
+        sensitive_media,
+        on_expand,
+        new,
+        revealed,
+        expanded,
+        on_reveal,
+        on_toggle_expanded,
+    } = props.clone();
+
+    let fullname = item.fullname();
+    let on_reveal = {
+        let fullname = fullname.clone();
+        Callback::from(move |_| on_reveal.emit(fullname.clone()))
+    };
+    let on_toggle_expanded = Callback::from(move |_| on_toggle_expanded.emit(fullname.clone()));
+
+    item.html(&CardContext {
+        live: live.as_deref(),
+        on_expand: &on_expand,
+        sensitive_media,
+        revealed,
+        on_reveal,
+        expanded,
+        on_toggle_expanded,
+        new,
+    })
+}
@@ -0,0 +1,280 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::rc::Rc;
+
+use gloo_events::EventListener;
+use web_sys::Element;
+use yew::prelude::*;
+
+use super::result_card::{self, ResultCard};
+use crate::live::LiveState;
+use crate::media::OnExpand;
+use crate::pushshift::RedditType;
+use crate::settings::SensitiveMedia;
+
+/// Number of cards that are rendered or skipped together
+const CHUNK_SIZE: usize = 50;
+/// Height of a card until real heights have been measured
+const ESTIMATED_CARD_HEIGHT: f64 = 150.0;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub items: Rc<Vec<Rc<RedditType>>>,
+    /// Changes when the items are a different list, rather than more of the same
+    pub generation: u32,
+    pub live: Rc<HashMap<String, Rc<LiveState>>>,
+    pub sensitive_media: SensitiveMedia,
+    pub on_expand: OnExpand,
+    /// Fullnames of items to mark as new
+    pub new_items: Rc<HashSet<String>>,
+    /// Only render cards near the viewport
+    pub virtualize: bool,
+}
+
+pub enum Msg {
+    Scroll,
+    Reveal(String),
+    ToggleExpanded(String),
+}
+
+/// Results container that only renders chunks of cards close to the viewport
+///
+/// Chunks outside the window are replaced by spacers of their last measured
+/// height. Content above the viewport changing size is compensated by the
+/// browser's scroll anchoring, so appending pages keeps the scroll position.
+pub struct ResultList {
+    container: NodeRef,
+    chunks: Vec<NodeRef>,
+    heights: Vec<Option<f64>>,
+    /// Average card height measured before chunks moved, as long as the list is the same
+    card_height: Option<f64>,
+    visible: Range<usize>,
+    items: Rc<Vec<Rc<RedditType>>>,
+    generation: u32,
+    /// Fullnames of revealed sensitive media and expanded bodies, kept here
+    /// because cards of chunks out of view are unmounted
+    revealed: HashSet<String>,
+    expanded: HashSet<String>,
+    on_reveal: Callback<String>,
+    on_toggle_expanded: Callback<String>,
+    _listeners: [EventListener; 2],
+}
+
+impl Component for ResultList {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let window = gloo_utils::window();
+        let listener = |event: &'static str| {
+            let link = ctx.link().clone();
+            EventListener::new(&window, event, move |_| link.send_message(Msg::Scroll))
+        };
+
+        let mut list = Self {
+            container: NodeRef::default(),
+            chunks: Vec::new(),
+            heights: Vec::new(),
+            card_height: None,
+            visible: 0..1,
+            items: ctx.props().items.clone(),
+            generation: ctx.props().generation,
+            revealed: HashSet::new(),
+            expanded: HashSet::new(),
+            on_reveal: ctx.link().callback(Msg::Reveal),
+            on_toggle_expanded: ctx.link().callback(Msg::ToggleExpanded),
+            _listeners: [listener("scroll"), listener("resize")],
+        };
+        list.resize_chunks();
+        list
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Msg::Scroll => {
+                if !ctx.props().virtualize {
+                    return false;
+                }
+                self.measure();
+                self.update_visible()
+            }
+            Msg::Reveal(fullname) => self.revealed.insert(fullname),
+            Msg::ToggleExpanded(fullname) => {
+                if !self.expanded.remove(&fullname) {
+                    self.expanded.insert(fullname);
+                }
+                true
+            }
+        }
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let props = ctx.props();
+        if props.generation != self.generation {
+            self.generation = props.generation;
+            self.revealed.clear();
+            self.expanded.clear();
+            self.card_height = None;
+        } else {
+            self.card_height = self.measured_card_height().or(self.card_height);
+        }
+
+        // Keep measurements of full chunks when results were only appended,
+        // items added in front move every chunk
+        let items = props.items.clone();
+        let common = self
+            .items
+            .iter()
+            .zip(items.iter())
+            .take_while(|(a, b)| Rc::ptr_eq(a, b))
+            .count();
+        self.heights.truncate(common / CHUNK_SIZE);
+        self.items = items;
+        self.resize_chunks();
+        self.update_visible();
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let card = |r: &Rc<RedditType>| {
+            let fullname = r.fullname();
+            let card = result_card::Props {
+                item: r.clone(),
+                live: props.live.get(&fullname).cloned(),
+                sensitive_media: props.sensitive_media,
+                on_expand: props.on_expand.clone(),
+                new: props.new_items.contains(&fullname),
+                revealed: self.revealed.contains(&fullname),
+                expanded: self.expanded.contains(&fullname),
+                on_reveal: self.on_reveal.clone(),
+                on_toggle_expanded: self.on_toggle_expanded.clone(),
+            };
+            html! {
+                <ResultCard key={fullname} ..card />
+            }
+        };
+
+        if !props.virtualize {
+            return html! {
+                <div>
+                    {for props.items.iter().map(card)}
+                </div>
+            };
+        }
+
+        let visible =
+            self.visible.start.min(self.chunks.len())..self.visible.end.min(self.chunks.len());
+        let spacer = |key: &'static str, range: Range<usize>| {
+            let height: f64 = range.map(|i| self.height(i)).sum();
+            html! {
+                <div key={key} style={format!("height: {}px", height)} />
+            }
+        };
+        let chunks = visible.clone().map(|i| {
+            let start = i * CHUNK_SIZE;
+            let end = (start + CHUNK_SIZE).min(props.items.len());
+            html! {
+                <div key={i} ref={self.chunks[i].clone()}>
+                    {for props.items[start..end].iter().map(card)}
+                </div>
+            }
+        });
+
+        html! {
+            <div ref={self.container.clone()}>
+                {spacer("top", 0..visible.start)}
+                {for chunks}
+                {spacer("bottom", visible.end..self.chunks.len())}
+            </div>
+        }
+    }
+
+    fn rendered(&mut self, ctx: &Context<Self>, _first_render: bool) {
+        // Newly rendered chunks may reveal that more or less fits on screen
+        if ctx.props().virtualize {
+            self.measure();
+            if self.update_visible() {
+                ctx.link().send_message(Msg::Scroll);
+            }
+        }
+    }
+}
+
+impl ResultList {
+    fn resize_chunks(&mut self) {
+        let count = self.items.len().div_ceil(CHUNK_SIZE);
+        self.chunks.resize_with(count, NodeRef::default);
+        self.heights.resize(count, None);
+    }
+
+    fn chunk_len(&self, i: usize) -> usize {
+        self.items
+            .len()
+            .saturating_sub(i * CHUNK_SIZE)
+            .min(CHUNK_SIZE)
+    }
+
+    /// Measured height of a chunk, or an estimate from the chunks measured so far
+    fn height(&self, i: usize) -> f64 {
+        if let Some(h) = self.heights.get(i).copied().flatten() {
+            return h;
+        }
+
+        let per_card = self
+            .measured_card_height()
+            .or(self.card_height)
+            .unwrap_or(ESTIMATED_CARD_HEIGHT);
+        per_card * self.chunk_len(i) as f64
+    }
+
+    fn measured_card_height(&self) -> Option<f64> {
+        let (total, cards) = self
+            .heights
+            .iter()
+            .enumerate()
+            .filter_map(|(i, h)| h.map(|h| (h, self.chunk_len(i))))
+            .fold((0.0, 0), |(total, cards), (h, n)| (total + h, cards + n));
+        (cards > 0).then(|| total / cards as f64)
+    }
+
+    fn measure(&mut self) {
+        for i in self.visible.clone() {
+            if let Some(e) = self.chunks.get(i).and_then(|c| c.cast::<Element>()) {
+                self.heights[i] = Some(e.get_bounding_client_rect().height());
+            }
+        }
+    }
+
+    /// Recompute which chunks are within a screen of the viewport, returns whether they changed
+    fn update_visible(&mut self) -> bool {
+        let top = match self.container.cast::<Element>() {
+            Some(e) => e.get_bounding_client_rect().top(),
+            None => return false,
+        };
+        let screen = gloo_utils::window()
+            .inner_height()
+            .ok()
+            .and_then(|h| h.as_f64())
+            .unwrap_or(1000.0);
+
+        let mut y = top;
+        let mut visible: Option<Range<usize>> = None;
+        for i in 0..self.chunks.len() {
+            let h = self.height(i);
+            if y + h >= -screen && y <= 2.0 * screen {
+                visible = Some(visible.map_or(i..i + 1, |v| v.start..i + 1));
+            }
+            y += h;
+        }
+        // Keep the last chunk when scrolled past the whole list
+        let visible = visible.unwrap_or_else(|| match self.chunks.len() {
+            n if n > 0 && y < -screen => n - 1..n,
+            _ => 0..1,
+        });
+
+        let changed = visible != self.visible;
+        self.visible = visible;
+        changed
+    }
+}
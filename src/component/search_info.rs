@@ -0,0 +1,30 @@
+use yew::prelude::*;
+
+use crate::pushshift::Metadata;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    pub metadata: Metadata,
+    /// Number of results shown
+    pub results: usize,
+}
+
+/// Search statistics sent by the API, and its warnings
+#[function_component(SearchInfo)]
+pub fn search_info(props: &Props) -> Html {
+    let Props { metadata, results } = props.clone();
+
+    let mut info = match metadata.total_results {
+        Some(total) => format!("{} of ~{} total results", results, total),
+        None => format!("{} results", results),
+    };
+    if let Some(ms) = metadata.execution_time_milliseconds {
+        info += &format!(", searched in {:.0} ms", ms);
+    }
+    html! {
+        <>
+            <div class="info">{info}</div>
+            { for metadata.warnings().into_iter().map(|w| html! { <div class="info error">{w}</div> }) }
+        </>
+    }
+}
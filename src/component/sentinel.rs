@@ -0,0 +1,59 @@
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{Element, IntersectionObserver, IntersectionObserverEntry, IntersectionObserverInit};
+use yew::prelude::*;
+
+#[derive(Clone, PartialEq, Properties)]
+pub struct Props {
+    /// Called with whether the sentinel is (close to being) on screen
+    pub on_change: Callback<bool>,
+}
+
+/// Invisible element that reports when it scrolls into view
+#[function_component(Sentinel)]
+pub fn sentinel(props: &Props) -> Html {
+    let node = use_node_ref();
+
+    {
+        let node = node.clone();
+        let on_change = props.on_change.clone();
+        use_effect_with_deps(
+            move |_| {
+                let callback = Closure::wrap(Box::new(move |entries: js_sys::Array| {
+                    let visible = entries
+                        .iter()
+                        .filter_map(|e| e.dyn_into::<IntersectionObserverEntry>().ok())
+                        .next_back()
+                        .map(|e| e.is_intersecting());
+                    if let Some(visible) = visible {
+                        on_change.emit(visible);
+                    }
+                }) as Box<dyn FnMut(js_sys::Array)>);
+
+                // Trigger a bit before the bottom is actually reached
+                let mut options = IntersectionObserverInit::new();
+                options.root_margin("0px 0px 400px 0px");
+                let observer = IntersectionObserver::new_with_options(
+                    callback.as_ref().unchecked_ref(),
+                    &options,
+                )
+                .ok();
+                if let (Some(observer), Some(element)) = (&observer, node.cast::<Element>()) {
+                    observer.observe(&element);
+                }
+
+                move || {
+                    if let Some(observer) = observer {
+                        observer.disconnect();
+                    }
+                    drop(callback);
+                }
+            },
+            (),
+        );
+    }
+
+    html! {
+        <div class="sentinel" ref={node} />
+    }
+}
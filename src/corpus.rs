@@ -0,0 +1,209 @@
+use js_sys::{Array, Function, Object, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::IdbTransactionMode;
+
+use crate::idb::{complete, describe, open, wait, AUTHOR_INDEX, CORPUS_STORE, SUBREDDIT_INDEX};
+use crate::params::{Endpoint, SearchParams};
+use crate::pushshift::RedditType;
+
+/// Source of searches of the corpus, as shown in the history
+pub static BACKEND: &str = "local corpus";
+
+/// A result as kept in the corpus, with the fields it is looked up by
+pub struct Record {
+    /// Fullname of the item, so each one is only kept once
+    id: String,
+    kind: &'static str,
+    subreddit: String,
+    author: String,
+    time: i64,
+    /// The item in the archive's JSON format
+    data: String,
+}
+
+impl Record {
+    pub fn new(r: &RedditType) -> Self {
+        Self {
+            id: r.fullname(),
+            kind: kind(r),
+            subreddit: r.subreddit().to_lowercase(),
+            author: r.author().to_lowercase(),
+            time: r.time(),
+            data: serde_json::to_string(r).unwrap(),
+        }
+    }
+
+    fn to_js(&self) -> Result<Object, JsValue> {
+        let object = Object::new();
+        Reflect::set(&object, &"id".into(), &self.id.as_str().into())?;
+        Reflect::set(&object, &"kind".into(), &self.kind.into())?;
+        Reflect::set(
+            &object,
+            &"subreddit".into(),
+            &self.subreddit.as_str().into(),
+        )?;
+        Reflect::set(&object, &"author".into(), &self.author.as_str().into())?;
+        Reflect::set(&object, &"created_utc".into(), &(self.time as f64).into())?;
+        Reflect::set(&object, &"data".into(), &self.data.as_str().into())?;
+        Ok(object)
+    }
+}
+
+fn kind(r: &RedditType) -> &'static str {
+    match r {
+        RedditType::Comment(_) => "comment",
+        RedditType::Submission(_) => "submission",
+    }
+}
+
+fn endpoint_kind(endpoint: &Endpoint) -> &'static str {
+    match endpoint {
+        Endpoint::Comment => "comment",
+        Endpoint::Submission => "submission",
+    }
+}
+
+/// Size of the corpus
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub items: usize,
+    /// Storage used by the whole site, if the browser tells
+    pub bytes: Option<u64>,
+}
+
+/// Items to remove from the corpus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Purge {
+    Subreddit(String),
+    Author(String),
+    All,
+}
+
+/// Add items to the corpus, replacing ones already in it
+pub async fn store(records: Vec<Record>) -> Result<(), String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db
+        .transaction_with_str_and_mode(CORPUS_STORE, IdbTransactionMode::Readwrite)
+        .map_err(describe)?;
+    let store = tx.object_store(CORPUS_STORE).map_err(describe)?;
+    for record in records {
+        let record = record.to_js().map_err(describe)?;
+        store.put(&record).map_err(describe)?;
+    }
+    complete(&tx).await.map_err(describe)
+}
+
+/// Items of the search's kind that may match the search form, as NDJSON
+///
+/// Records are parsed and filtered by the worker, there can be many of them.
+pub async fn search(params: &SearchParams) -> Result<String, String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db.transaction_with_str(CORPUS_STORE).map_err(describe)?;
+    let store = tx.object_store(CORPUS_STORE).map_err(describe)?;
+
+    // Narrow the search down with an index where possible
+    let subreddit = params.subreddit.trim().to_lowercase();
+    let author = params.author.trim().to_lowercase();
+    let request = if !subreddit.is_empty() {
+        let index = store.index(SUBREDDIT_INDEX).map_err(describe)?;
+        index.get_all_with_key(&subreddit.into())
+    } else if !author.is_empty() {
+        let index = store.index(AUTHOR_INDEX).map_err(describe)?;
+        index.get_all_with_key(&author.into())
+    } else {
+        store.get_all()
+    }
+    .map_err(describe)?;
+    let records: Array = wait(&request).await.map_err(describe)?.unchecked_into();
+
+    let kind = endpoint_kind(&params.endpoint);
+    let data: Vec<String> = records
+        .iter()
+        .filter(|r| field(r, "kind").as_string().as_deref() == Some(kind))
+        .filter_map(|r| field(&r, "data").as_string())
+        .collect();
+    Ok(data.join("\n"))
+}
+
+/// Everything in the corpus, as NDJSON
+pub async fn load() -> Result<String, String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db.transaction_with_str(CORPUS_STORE).map_err(describe)?;
+    let store = tx.object_store(CORPUS_STORE).map_err(describe)?;
+    let records: Array = wait(&store.get_all().map_err(describe)?)
+        .await
+        .map_err(describe)?
+        .unchecked_into();
+
+    let data: Vec<String> = records
+        .iter()
+        .filter_map(|r| field(&r, "data").as_string())
+        .collect();
+    Ok(data.join("\n"))
+}
+
+pub async fn stats() -> Result<Stats, String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db.transaction_with_str(CORPUS_STORE).map_err(describe)?;
+    let store = tx.object_store(CORPUS_STORE).map_err(describe)?;
+    let count = wait(&store.count().map_err(describe)?)
+        .await
+        .map_err(describe)?;
+
+    Ok(Stats {
+        items: count.as_f64().unwrap_or_default() as usize,
+        bytes: storage_usage().await.ok(),
+    })
+}
+
+/// Bytes the browser stores for this site
+async fn storage_usage() -> Result<u64, JsValue> {
+    // Storage manager bindings of web-sys are unstable, so go through JS
+    let storage = Reflect::get(&gloo_utils::window().navigator(), &"storage".into())?;
+    let estimate: Function = Reflect::get(&storage, &"estimate".into())?.dyn_into()?;
+    let promise: Promise = estimate.call0(&storage)?.dyn_into()?;
+    let estimate = JsFuture::from(promise).await?;
+    Reflect::get(&estimate, &"usage".into())?
+        .as_f64()
+        .map(|u| u as u64)
+        .ok_or_else(|| JsValue::from("no usage estimate"))
+}
+
+/// Remove items from the corpus, returns how many were removed
+pub async fn purge(purge: Purge) -> Result<usize, String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db
+        .transaction_with_str_and_mode(CORPUS_STORE, IdbTransactionMode::Readwrite)
+        .map_err(describe)?;
+    let store = tx.object_store(CORPUS_STORE).map_err(describe)?;
+
+    let (index, key) = match purge {
+        Purge::Subreddit(s) => (SUBREDDIT_INDEX, s),
+        Purge::Author(a) => (AUTHOR_INDEX, a),
+        Purge::All => {
+            let count = store.count().map_err(describe)?;
+            store.clear().map_err(describe)?;
+            let count = wait(&count).await.map_err(describe)?;
+            complete(&tx).await.map_err(describe)?;
+            return Ok(count.as_f64().unwrap_or_default() as usize);
+        }
+    };
+
+    let key = JsValue::from(key.trim().to_lowercase());
+    let request = store
+        .index(index)
+        .map_err(describe)?
+        .get_all_keys_with_key(&key)
+        .map_err(describe)?;
+    let ids: Array = wait(&request).await.map_err(describe)?.unchecked_into();
+    for id in ids.iter() {
+        store.delete(&id).map_err(describe)?;
+    }
+    complete(&tx).await.map_err(describe)?;
+    Ok(ids.length() as usize)
+}
+
+fn field(record: &JsValue, name: &str) -> JsValue {
+    Reflect::get(record, &name.into()).unwrap_or(JsValue::UNDEFINED)
+}
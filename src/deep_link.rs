@@ -0,0 +1,171 @@
+use url::Url;
+use wasm_bindgen::JsValue;
+
+use crate::filter::{Filters, RemovedFilter};
+use crate::params::{format_time, parse_time, Endpoint, SearchParams};
+
+/// Search state encoded in the page's query string
+///
+/// Times are stored as unix timestamps so links work across timezones.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeepLink {
+    pub params: SearchParams,
+    pub filters: Filters,
+    /// Run the search as soon as the page is loaded
+    pub run: bool,
+}
+
+impl DeepLink {
+    /// Read a deep link from the current page URL, `None` if it has no search parameters
+    pub fn from_location(tz_offset: i64) -> Option<Self> {
+        let href = gloo_utils::window().location().href().ok()?;
+        Self::from_url(&Url::parse(&href).ok()?, tz_offset)
+    }
+
+    pub fn from_url(url: &Url, tz_offset: i64) -> Option<Self> {
+        let mut found = false;
+        let mut params = SearchParams {
+            endpoint: Endpoint::Comment,
+            subreddit: String::new(),
+            author: String::new(),
+            query: String::new(),
+            time_start: String::new(),
+            time_end: String::new(),
+        };
+        let mut filters = Filters {
+            removed: RemovedFilter::All,
+        };
+        let mut run = false;
+
+        for (k, v) in url.query_pairs() {
+            let time = || {
+                v.parse()
+                    .ok()
+                    .and_then(|ts| format_time(ts, tz_offset))
+                    .unwrap_or_default()
+            };
+            match k.as_ref() {
+                "type" => match Endpoint::from_id(&v) {
+                    Some(e) => params.endpoint = e,
+                    None => continue,
+                },
+                "subreddit" => params.subreddit = v.into_owned(),
+                "author" => params.author = v.into_owned(),
+                "q" => params.query = v.into_owned(),
+                "after" => params.time_start = time(),
+                "before" => params.time_end = time(),
+                "removed" => match RemovedFilter::from_id(&v) {
+                    Some(r) => filters.removed = r,
+                    None => continue,
+                },
+                "run" => run = v == "1",
+                _ => continue,
+            }
+            found = true;
+        }
+
+        found.then_some(Self {
+            params,
+            filters,
+            run,
+        })
+    }
+
+    /// Page URL that restores this search
+    pub fn url(&self, tz_offset: i64) -> Option<String> {
+        let href = gloo_utils::window().location().href().ok()?;
+        let page = Url::parse(&href).ok()?;
+        Some(self.to_url(page, tz_offset).to_string())
+    }
+
+    /// `page` with its query replaced by this search
+    pub fn to_url(&self, mut url: Url, tz_offset: i64) -> Url {
+        url.set_fragment(None);
+        url.set_query(None);
+
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("type", self.params.endpoint.id());
+            for (k, v) in [
+                ("subreddit", &self.params.subreddit),
+                ("author", &self.params.author),
+                ("q", &self.params.query),
+            ] {
+                if !v.is_empty() {
+                    query.append_pair(k, v);
+                }
+            }
+            for (k, v) in [
+                ("after", &self.params.time_start),
+                ("before", &self.params.time_end),
+            ] {
+                if let Some(ts) = parse_time(v, tz_offset) {
+                    query.append_pair(k, &ts.to_string());
+                }
+            }
+            if self.filters.removed != RemovedFilter::All {
+                query.append_pair("removed", self.filters.removed.id());
+            }
+            if self.run {
+                query.append_pair("run", "1");
+            }
+        }
+
+        url
+    }
+
+    /// Show this search in the address bar, replacing the current history entry
+    pub fn replace_location(&self, tz_offset: i64, state: u32) {
+        if let (Some(url), Ok(history)) = (self.url(tz_offset), gloo_utils::window().history()) {
+            let _ = history.replace_state_with_url(&JsValue::from(state), "", Some(&url));
+        }
+    }
+
+    /// Show this search in the address bar as a new history entry
+    pub fn push_location(&self, tz_offset: i64, state: u32) {
+        if let (Some(url), Ok(history)) = (self.url(tz_offset), gloo_utils::window().history()) {
+            let _ = history.push_state_with_url(&JsValue::from(state), "", Some(&url));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page() -> Url {
+        Url::parse("https://example.com/app/?old=1#results").unwrap()
+    }
+
+    #[test]
+    fn round_trip() {
+        let link = DeepLink {
+            params: SearchParams {
+                endpoint: Endpoint::Submission,
+                subreddit: "rust".to_owned(),
+                author: "some one".to_owned(),
+                query: "\"borrow checker\" -unsafe & more".to_owned(),
+                time_start: "2023-01-02 03:04".to_owned(),
+                time_end: String::new(),
+            },
+            filters: Filters {
+                removed: RemovedFilter::Only,
+            },
+            run: false,
+        };
+        // Times are stored as timestamps and read back in another timezone
+        let url = link.to_url(page(), 60);
+        assert!(!url.as_str().contains("run="));
+        let mut expected = link.clone();
+        expected.params.time_start = "2023-01-02 04:04".to_owned();
+        assert_eq!(DeepLink::from_url(&url, 120), Some(expected));
+
+        let run = DeepLink { run: true, ..link };
+        assert_eq!(DeepLink::from_url(&run.to_url(page(), 60), 60), Some(run));
+    }
+
+    #[test]
+    fn no_search_parameters() {
+        assert_eq!(DeepLink::from_url(&page(), 0), None);
+    }
+}
@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+
+use crate::fetch::FetchError;
+
+/// Requests beyond this are dropped, oldest first
+const MAX_ENTRIES: usize = 50;
+/// Responses are large, so only the latest ones are kept
+const MAX_BODIES: usize = 5;
+
+/// An API request and what came back
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestLog {
+    /// Id of the worker job parsing the response
+    pub id: u32,
+    pub url: String,
+    pub backend: String,
+    /// Unix timestamp of when the request was sent
+    pub time: i64,
+    /// Failed requests before this one, for retries
+    pub attempt: u32,
+    pub status: Option<u16>,
+    pub latency_ms: Option<f64>,
+    pub bytes: Option<usize>,
+    pub records: Option<usize>,
+    pub warnings: Vec<String>,
+    pub error: Option<String>,
+    /// Whether the response body is still kept
+    pub has_body: bool,
+}
+
+impl RequestLog {
+    /// Command that sends the same request
+    pub fn curl(&self) -> String {
+        format!("curl '{}'", self.url.replace('\'', r"'\''"))
+    }
+}
+
+/// Requests of this session, newest first
+#[derive(Default)]
+pub struct Diagnostics {
+    entries: VecDeque<RequestLog>,
+    bodies: VecDeque<(u32, Rc<str>)>,
+}
+
+impl Diagnostics {
+    pub fn entries(&self) -> Vec<RequestLog> {
+        self.entries.iter().cloned().collect()
+    }
+
+    pub fn get(&self, id: u32) -> Option<&RequestLog> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    pub fn body(&self, id: u32) -> Option<Rc<str>> {
+        self.bodies
+            .iter()
+            .find(|(i, _)| *i == id)
+            .map(|(_, b)| b.clone())
+    }
+
+    pub fn start(&mut self, id: u32, url: String, backend: String, attempt: u32) {
+        self.entries.push_front(RequestLog {
+            id,
+            url,
+            backend,
+            time: (js_sys::Date::now() / 1000.0) as i64,
+            attempt,
+            status: None,
+            latency_ms: None,
+            bytes: None,
+            records: None,
+            warnings: Vec::new(),
+            error: None,
+            has_body: false,
+        });
+        self.entries.truncate(MAX_ENTRIES);
+    }
+
+    /// Record the response, or why there was none
+    pub fn received(
+        &mut self,
+        id: u32,
+        latency_ms: f64,
+        result: &Result<(u16, String), FetchError>,
+    ) {
+        let entry = match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(e) => e,
+            None => return,
+        };
+        entry.latency_ms = Some(latency_ms);
+        let body = match result {
+            Ok((status, body)) => {
+                entry.status = Some(*status);
+                Some(body.as_str())
+            }
+            // Error responses are kept too, they are what needs looking into
+            Err(e) => {
+                entry.status = e.status();
+                entry.error = Some(e.to_string());
+                e.body()
+            }
+        };
+        if let Some(body) = body {
+            entry.bytes = Some(body.len());
+            entry.has_body = true;
+            self.bodies.push_front((id, body.into()));
+            if self.bodies.len() > MAX_BODIES {
+                if let Some((dropped, _)) = self.bodies.pop_back() {
+                    if let Some(e) = self.entries.iter_mut().find(|e| e.id == dropped) {
+                        e.has_body = false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Record how the response was parsed
+    pub fn parsed(&mut self, id: u32, result: Result<(usize, Vec<String>), &str>) {
+        let entry = match self.entries.iter_mut().find(|e| e.id == id) {
+            Some(e) => e,
+            None => return,
+        };
+        match result {
+            Ok((records, warnings)) => {
+                entry.records = Some(records);
+                entry.warnings = warnings;
+            }
+            Err(e) if entry.error.is_none() => entry.error = Some(e.to_owned()),
+            Err(_) => {}
+        }
+    }
+}
+
+/// Put text on the clipboard
+pub async fn copy(text: &str) -> Result<(), JsValue> {
+    // Clipboard bindings of web-sys are unstable, so go through JS
+    let clipboard = Reflect::get(&gloo_utils::window().navigator(), &"clipboard".into())?;
+    let write: Function = Reflect::get(&clipboard, &"writeText".into())?.dyn_into()?;
+    let promise: Promise = write.call1(&clipboard, &text.into())?.dyn_into()?;
+    JsFuture::from(promise).await?;
+    Ok(())
+}
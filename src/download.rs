@@ -0,0 +1,48 @@
+use gloo_timers::callback::Timeout;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Blob, BlobPropertyBag, HtmlAnchorElement, Url};
+
+/// Time a download or new tab gets to load an object URL before it is released
+const REVOKE_DELAY_MS: u32 = 60_000;
+
+fn object_url(mime: &str, contents: &str) -> Result<String, JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    Url::create_object_url_with_blob(&blob)
+}
+
+/// Offer `contents` as a file download
+pub fn download(filename: &str, mime: &str, contents: &str) -> Result<(), JsValue> {
+    let url = object_url(mime, contents)?;
+
+    let anchor: HtmlAnchorElement = gloo_utils::document()
+        .create_element("a")?
+        .dyn_into()
+        .map_err(JsValue::from)?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+
+    // Browsers may start the download after the click returns
+    revoke_later(url);
+    Ok(())
+}
+
+/// Show `contents` in a new tab
+pub fn open(mime: &str, contents: &str) -> Result<(), JsValue> {
+    let url = object_url(mime, contents)?;
+    gloo_utils::window().open_with_url_and_target(&url, "_blank")?;
+
+    // The tab loads the URL on its own time
+    revoke_later(url);
+    Ok(())
+}
+
+fn revoke_later(url: String) {
+    Timeout::new(REVOKE_DELAY_MS, move || {
+        let _ = Url::revoke_object_url(&url);
+    })
+    .forget();
+}
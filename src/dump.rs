@@ -0,0 +1,308 @@
+use std::cell::Cell;
+use std::rc::Rc;
+
+use gloo_file::futures::read_as_bytes;
+use gloo_file::Blob;
+use ruzstd::decoding::errors::FrameDecoderError;
+use ruzstd::decoding::FrameDecoder;
+use serde::{Deserialize, Serialize};
+
+use crate::params::{parse_time, Endpoint, SearchParams};
+use crate::pushshift::RedditType;
+
+/// Bytes read from the file at a time
+const READ_SIZE: u64 = 4 * 1024 * 1024;
+/// Decompressed bytes taken from the decoder at a time
+const OUTPUT_SIZE: usize = 8 * 1024 * 1024;
+/// Largest zstd frame header, a new frame is only started with this much input
+const MAX_FRAME_HEADER: usize = 18;
+/// Scanning stops after this many matches to keep memory in check
+pub const MAX_MATCHES: usize = 50_000;
+
+static ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+/// The search form's parameters applied to dump records
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpFilter {
+    subreddit: String,
+    author: String,
+    words: Vec<String>,
+    after: Option<i64>,
+    before: Option<i64>,
+}
+
+impl DumpFilter {
+    pub fn new(params: &SearchParams, tz_offset: i64) -> Self {
+        Self {
+            subreddit: params.subreddit.trim().to_lowercase(),
+            author: params.author.trim().to_lowercase(),
+            words: params
+                .query
+                .split_whitespace()
+                .map(str::to_lowercase)
+                .collect(),
+            after: parse_time(&params.time_start, tz_offset),
+            before: parse_time(&params.time_end, tz_offset),
+        }
+    }
+
+    /// Cheap check on the raw record, to skip parsing most of them
+    fn may_match(&self, line: &[u8]) -> bool {
+        contains_ignore_case(line, self.subreddit.as_bytes())
+            && contains_ignore_case(line, self.author.as_bytes())
+    }
+
+    pub fn matches(&self, r: &RedditType) -> bool {
+        if !self.subreddit.is_empty() && !r.subreddit().eq_ignore_ascii_case(&self.subreddit) {
+            return false;
+        }
+        if !self.author.is_empty() && !r.author().eq_ignore_ascii_case(&self.author) {
+            return false;
+        }
+        if self.after.is_some_and(|t| r.time() <= t) || self.before.is_some_and(|t| r.time() >= t) {
+            return false;
+        }
+        if self.words.is_empty() {
+            return true;
+        }
+
+        let text = format!("{} {}", r.title().unwrap_or_default(), r.content()).to_lowercase();
+        self.words.iter().all(|w| text.contains(w.as_str()))
+    }
+}
+
+fn contains_ignore_case(haystack: &[u8], needle: &[u8]) -> bool {
+    needle.is_empty()
+        || haystack
+            .windows(needle.len())
+            .any(|w| w.eq_ignore_ascii_case(needle))
+}
+
+/// How far a scan got, sent along with the matches found since the last report
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Progress {
+    pub read: u64,
+    pub total: u64,
+    pub matches: Vec<RedditType>,
+    /// Records that could not be parsed
+    pub skipped: usize,
+}
+
+/// Splits decompressed data into records and keeps the matching ones
+struct Scanner {
+    /// Kind of the dump's records, known once one was read
+    endpoint: Option<Endpoint>,
+    filter: DumpFilter,
+    tz_offset: i64,
+    pending: Vec<u8>,
+    matches: Vec<RedditType>,
+    matched: usize,
+    skipped: usize,
+}
+
+impl Scanner {
+    fn push(&mut self, bytes: &[u8]) {
+        self.pending.extend_from_slice(bytes);
+        let end = match self.pending.iter().rposition(|b| *b == b'\n') {
+            Some(end) => end + 1,
+            None => return,
+        };
+        let rest = self.pending.split_off(end);
+        let complete = std::mem::replace(&mut self.pending, rest);
+        for line in complete.split(|b| *b == b'\n') {
+            self.record(line);
+        }
+    }
+
+    fn finish(&mut self) {
+        let rest = std::mem::take(&mut self.pending);
+        self.record(&rest);
+    }
+
+    fn full(&self) -> bool {
+        self.matched >= MAX_MATCHES
+    }
+
+    fn record(&mut self, line: &[u8]) {
+        if self.full() || line.iter().all(u8::is_ascii_whitespace) || !self.filter.may_match(line) {
+            return;
+        }
+        let line = match std::str::from_utf8(line) {
+            Ok(line) => line,
+            Err(_) => {
+                self.skipped += 1;
+                return;
+            }
+        };
+
+        // A dump holds one kind, whatever the search form is set to
+        let endpoint = match &self.endpoint {
+            Some(e) => e,
+            None => match serde_json::from_str(line) {
+                Ok(value) => self.endpoint.insert(Endpoint::detect(&value)),
+                Err(_) => {
+                    self.skipped += 1;
+                    return;
+                }
+            },
+        };
+        match endpoint.parse(format!("{{\"data\":[{}]}}", line), self.tz_offset) {
+            Ok(items) => {
+                for item in items.into_iter().filter(|r| self.filter.matches(r)) {
+                    self.matches.push(item);
+                    self.matched += 1;
+                }
+            }
+            Err(_) => self.skipped += 1,
+        }
+    }
+
+    fn progress(&mut self, read: u64, total: u64) -> Progress {
+        Progress {
+            read,
+            total,
+            matches: std::mem::take(&mut self.matches),
+            skipped: std::mem::replace(&mut self.skipped, 0),
+        }
+    }
+}
+
+fn describe(e: FrameDecoderError) -> String {
+    match e {
+        FrameDecoderError::WindowSizeTooBig { requested } => format!(
+            "the dump needs a {} MB decompression window, more than the browser can use. \
+             Recompress it with a smaller window (zstd -d --long=31 -c dump.zst | zstd -o small.zst) \
+             or decompress it and pick the NDJSON file",
+            requested / (1024 * 1024)
+        ),
+        e => format!("unable to decompress: {}", e),
+    }
+}
+
+/// Decompress as much of `input` as possible into the scanner
+fn decode_available(
+    decoder: &mut FrameDecoder,
+    input: &mut Vec<u8>,
+    output: &mut [u8],
+    at_end: bool,
+    scanner: &mut Scanner,
+) -> Result<(), String> {
+    loop {
+        // Dumps may consist of several frames
+        if decoder.is_finished() && decoder.can_collect() == 0 {
+            if input.is_empty() || input.len() < MAX_FRAME_HEADER && !at_end {
+                return Ok(());
+            }
+            *decoder = FrameDecoder::new();
+        }
+        let (read, written) = decoder.decode_from_to(input, output).map_err(describe)?;
+        input.drain(..read.min(input.len()));
+        scanner.push(&output[..written]);
+        if read == 0 && written == 0 {
+            return Ok(());
+        }
+    }
+}
+
+/// Stream a zstd compressed or plain NDJSON dump through the filter
+///
+/// Reports progress after each part of the file that was read, returns whether
+/// the scan was stopped early by `cancel` or by reaching `MAX_MATCHES`.
+pub async fn scan(
+    file: Blob,
+    filter: DumpFilter,
+    tz_offset: i64,
+    cancel: Rc<Cell<bool>>,
+    on_progress: impl Fn(Progress),
+) -> Result<bool, String> {
+    let mut scanner = Scanner {
+        endpoint: None,
+        filter,
+        tz_offset,
+        pending: Vec::new(),
+        matches: Vec::new(),
+        matched: 0,
+        skipped: 0,
+    };
+    let total = file.size();
+    let mut offset = 0;
+    let mut compressed = None;
+    let mut input: Vec<u8> = Vec::new();
+    let mut output = vec![0; OUTPUT_SIZE];
+    let mut decoder = FrameDecoder::new();
+
+    while offset < total {
+        if cancel.get() || scanner.full() {
+            return Ok(true);
+        }
+
+        let end = (offset + READ_SIZE).min(total);
+        let bytes = read_as_bytes(&file.slice(offset, end))
+            .await
+            .map_err(|e| e.to_string())?;
+        offset = end;
+        let compressed = *compressed.get_or_insert_with(|| bytes.starts_with(&ZSTD_MAGIC));
+
+        if !compressed {
+            scanner.push(&bytes);
+        } else {
+            input.extend_from_slice(&bytes);
+            decode_available(
+                &mut decoder,
+                &mut input,
+                &mut output,
+                offset == total,
+                &mut scanner,
+            )?;
+        }
+
+        on_progress(scanner.progress(offset, total));
+    }
+
+    if !input.is_empty() || !decoder.is_finished() {
+        return Err("the file ends in the middle of a compressed frame".to_owned());
+    }
+    scanner.finish();
+    let full = scanner.full();
+    on_progress(scanner.progress(offset, total));
+    Ok(full)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scanner(subreddit: &str) -> Scanner {
+        let params = SearchParams {
+            endpoint: Endpoint::Comment,
+            subreddit: subreddit.to_owned(),
+            author: String::new(),
+            query: String::new(),
+            time_start: String::new(),
+            time_end: String::new(),
+        };
+        Scanner {
+            endpoint: None,
+            filter: DumpFilter::new(&params, 0),
+            tz_offset: 0,
+            pending: Vec::new(),
+            matches: Vec::new(),
+            matched: 0,
+            skipped: 0,
+        }
+    }
+
+    #[test]
+    fn submissions_whatever_the_form_says() {
+        let mut scanner = scanner("rust");
+        scanner.push(
+            br#"{"id":"a1","author":"x","title":"One","selftext":"","is_self":true,"thumbnail":"self","url":"https://example.com/","created_utc":1600000000,"subreddit":"rust"}
+{"id":"a2","author":"y","title":"Two","selftext":"","is_self":true,"thumbnail":"self","url":"https://example.com/","created_utc":1600000001,"subreddit":"golang"}
+"#,
+        );
+        scanner.finish();
+        assert_eq!(scanner.skipped, 0);
+        assert_eq!(scanner.matches.len(), 1);
+        assert_eq!(scanner.matches[0].fullname(), "t3_a1");
+    }
+}
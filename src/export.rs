@@ -0,0 +1,321 @@
+use std::fmt::Display;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::pushshift::{format_timestamp, RedditType};
+
+/// File formats results can be exported as
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    Ndjson,
+    Csv,
+    Markdown,
+}
+
+static JSON_STR: &str = "JSON";
+static NDJSON_STR: &str = "NDJSON";
+static CSV_STR: &str = "CSV";
+static MARKDOWN_STR: &str = "Markdown";
+
+impl ExportFormat {
+    pub fn list() -> Vec<String> {
+        vec![
+            JSON_STR.into(),
+            NDJSON_STR.into(),
+            CSV_STR.into(),
+            MARKDOWN_STR.into(),
+        ]
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Ndjson => "ndjson",
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+
+    pub fn mime(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::Ndjson => "application/x-ndjson",
+            Self::Csv => "text/csv",
+            Self::Markdown => "text/markdown",
+        }
+    }
+}
+
+impl Display for ExportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json => write!(f, "{}", JSON_STR),
+            Self::Ndjson => write!(f, "{}", NDJSON_STR),
+            Self::Csv => write!(f, "{}", CSV_STR),
+            Self::Markdown => write!(f, "{}", MARKDOWN_STR),
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            x if x == JSON_STR => Ok(Self::Json),
+            x if x == NDJSON_STR => Ok(Self::Ndjson),
+            x if x == CSV_STR => Ok(Self::Csv),
+            x if x == MARKDOWN_STR => Ok(Self::Markdown),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A column of CSV exports
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Column {
+    Id,
+    Kind,
+    Subreddit,
+    Author,
+    Created,
+    Title,
+    Content,
+    Permalink,
+    Removed,
+}
+
+impl Column {
+    pub fn all() -> [Self; 9] {
+        [
+            Self::Id,
+            Self::Kind,
+            Self::Subreddit,
+            Self::Author,
+            Self::Created,
+            Self::Title,
+            Self::Content,
+            Self::Permalink,
+            Self::Removed,
+        ]
+    }
+
+    /// Header of the column, archive field names where there is one
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Id => "name",
+            Self::Kind => "kind",
+            Self::Subreddit => "subreddit",
+            Self::Author => "author",
+            Self::Created => "created_utc",
+            Self::Title => "title",
+            Self::Content => "body",
+            Self::Permalink => "permalink",
+            Self::Removed => "removed",
+        }
+    }
+
+    fn value(&self, r: &RedditType) -> String {
+        match self {
+            Self::Id => r.fullname(),
+            Self::Kind => match r {
+                RedditType::Comment(_) => "comment".to_owned(),
+                RedditType::Submission(_) => "submission".to_owned(),
+            },
+            Self::Subreddit => r.subreddit().to_owned(),
+            Self::Author => r.author().to_owned(),
+            Self::Created => r.time().to_string(),
+            Self::Title => r.title().unwrap_or_default().to_owned(),
+            Self::Content => r.content().to_owned(),
+            Self::Permalink => r.permalink(),
+            Self::Removed => r.removal().is_removed().to_string(),
+        }
+    }
+}
+
+/// Export format and CSV columns last used
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub columns: Vec<Column>,
+}
+
+impl ExportOptions {
+    pub fn load() -> Self {
+        let format = match LocalStorage::get("export_format")
+            .map(|s: String| ExportFormat::from_str(s.as_str()))
+        {
+            Ok(Ok(f)) => f,
+            _ => ExportFormat::Json,
+        };
+        let columns =
+            LocalStorage::get("export_columns").unwrap_or_else(|_| Column::all().to_vec());
+
+        ExportOptions { format, columns }
+    }
+
+    pub fn store(&self) {
+        LocalStorage::set("export_format", self.format.to_string()).unwrap();
+        LocalStorage::set("export_columns", &self.columns).unwrap();
+    }
+
+    /// Include or leave out a CSV column, keeping columns in their usual order
+    pub fn set_column(&mut self, column: Column, enabled: bool) {
+        self.columns.retain(|c| *c != column);
+        if enabled {
+            self.columns.push(column);
+        }
+        self.columns
+            .sort_by_key(|c| Column::all().iter().position(|x| x == c));
+    }
+
+    /// Name of the downloaded file
+    pub fn filename(&self) -> String {
+        format!("results.{}", self.format.extension())
+    }
+
+    /// Render the results in the selected format
+    pub fn export(&self, results: &[Rc<RedditType>]) -> String {
+        match self.format {
+            ExportFormat::Json => json(results),
+            ExportFormat::Ndjson => ndjson(results),
+            ExportFormat::Csv => csv(results, &self.columns),
+            ExportFormat::Markdown => markdown(results),
+        }
+    }
+}
+
+/// Same envelope as the archive's API responses
+fn json(results: &[Rc<RedditType>]) -> String {
+    let data: Vec<&RedditType> = results.iter().map(Rc::as_ref).collect();
+    serde_json::to_string_pretty(&serde_json::json!({ "data": data })).unwrap()
+}
+
+fn ndjson(results: &[Rc<RedditType>]) -> String {
+    results
+        .iter()
+        .map(|r| serde_json::to_string(r.as_ref()).unwrap() + "\n")
+        .collect()
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+fn csv(results: &[Rc<RedditType>], columns: &[Column]) -> String {
+    let row = |fields: Vec<String>| {
+        let fields: Vec<_> = fields.iter().map(|f| csv_field(f)).collect();
+        fields.join(",") + "\r\n"
+    };
+
+    let mut out = row(columns.iter().map(|c| c.name().to_owned()).collect());
+    for r in results {
+        out += &row(columns.iter().map(|c| c.value(r)).collect());
+    }
+    out
+}
+
+fn quote(text: &str) -> String {
+    text.lines()
+        .map(|l| {
+            if l.is_empty() {
+                ">\n".to_owned()
+            } else {
+                format!("> {}\n", l)
+            }
+        })
+        .collect()
+}
+
+/// Escape characters Markdown would read as formatting
+fn escape_markdown(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '(' | ')' | '#' | '|' | '<' | '>' | '~'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn markdown(results: &[Rc<RedditType>]) -> String {
+    let mut out = String::new();
+    for r in results {
+        let permalink = r.permalink();
+        if let Some(title) = r.title() {
+            out += &format!("### [{}]({})\n\n", escape_markdown(title), permalink);
+        }
+        out += &format!(
+            "**r/{}** · **u/{}** · {} UTC · [permalink]({})\n\n",
+            escape_markdown(r.subreddit()),
+            escape_markdown(r.author()),
+            format_timestamp(r.time(), 0),
+            permalink
+        );
+        if !r.content().is_empty() {
+            out += &quote(r.content());
+            out += "\n";
+        }
+        out += "---\n\n";
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn submission(title: &str, author: &str) -> Rc<RedditType> {
+        let data = json!({
+            "id": "abc",
+            "author": author,
+            "title": title,
+            "selftext": "",
+            "is_self": true,
+            "thumbnail": "self",
+            "url": "https://www.reddit.com/r/rust/comments/abc/",
+            "created_utc": 1600000000,
+            "subreddit": "rust",
+        });
+        Rc::new(RedditType::submission(data, 0).unwrap())
+    }
+
+    #[test]
+    fn csv_quotes_special_fields() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_rows() {
+        let results = [submission("Hello, world", "someone")];
+        let out = csv(&results, &[Column::Author, Column::Title]);
+        assert_eq!(out, "author,title\r\nsomeone,\"Hello, world\"\r\n");
+    }
+
+    #[test]
+    fn markdown_escapes_titles_and_authors() {
+        assert_eq!(escape_markdown("a [b] | *c*"), r"a \[b\] \| \*c\*");
+
+        let results = [submission("[Help] *urgent* | a_b", "user_name")];
+        let out = markdown(&results);
+        assert!(out.starts_with(r"### [\[Help\] \*urgent\* \| a\_b](https://"));
+        assert!(out.contains(r"**u/user\_name**"));
+    }
+}
@@ -8,15 +8,21 @@ use web_sys::{Request, RequestInit, RequestMode, Response};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum FetchError {
-    InvalidJsValue { err: JsValue },
-    BadStatus { code: u16 },
+    InvalidJsValue {
+        err: JsValue,
+    },
+    /// The response body is kept, it often tells what went wrong
+    BadStatus {
+        code: u16,
+        body: String,
+    },
 }
 
 impl Display for FetchError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::InvalidJsValue { err } => write!(f, "invalid JS value: {:?}", err),
-            Self::BadStatus { code } => write!(f, "received status code: {}", code),
+            Self::BadStatus { code, .. } => write!(f, "received status code: {}", code),
         }
     }
 }
@@ -29,7 +35,30 @@ impl From<JsValue> for FetchError {
     }
 }
 
+impl FetchError {
+    /// HTTP status of the response, if there was one
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Self::BadStatus { code, .. } => Some(*code),
+            Self::InvalidJsValue { .. } => None,
+        }
+    }
+
+    /// Body of the response, if there was one
+    pub fn body(&self) -> Option<&str> {
+        match self {
+            Self::BadStatus { body, .. } => Some(body),
+            Self::InvalidJsValue { .. } => None,
+        }
+    }
+}
+
 pub async fn fetch(url: String) -> Result<String, FetchError> {
+    fetch_with_status(url).await.map(|(_, text)| text)
+}
+
+/// Fetch `url` and keep the HTTP status of the response
+pub async fn fetch_with_status(url: String) -> Result<(u16, String), FetchError> {
     log::info!("Pushshift URL: {}", &url);
     let mut opts = RequestInit::new();
     opts.method("GET");
@@ -41,12 +70,13 @@ pub async fn fetch(url: String) -> Result<String, FetchError> {
     let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
     let resp: Response = resp_value.dyn_into().unwrap();
 
+    let text = JsFuture::from(resp.text()?).await?;
+    let text = text.as_string().unwrap();
     if !resp.ok() {
         return Err(FetchError::BadStatus {
             code: resp.status(),
+            body: text,
         });
     }
-
-    let text = JsFuture::from(resp.text()?).await?;
-    Ok(text.as_string().unwrap())
+    Ok((resp.status(), text))
 }
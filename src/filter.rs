@@ -0,0 +1,96 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::pushshift::RedditType;
+
+/// Client-side filters applied to already fetched results
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Filters {
+    pub removed: RemovedFilter,
+}
+
+impl Filters {
+    pub fn load() -> Self {
+        let removed = match LocalStorage::get("filter_removed")
+            .map(|s: String| RemovedFilter::from_str(s.as_str()))
+        {
+            Ok(Ok(r)) => r,
+            _ => RemovedFilter::All,
+        };
+
+        Filters { removed }
+    }
+
+    pub fn store(&self) {
+        LocalStorage::set("filter_removed", self.removed.to_string()).unwrap();
+    }
+
+    pub fn matches(&self, r: &RedditType) -> bool {
+        match self.removed {
+            RemovedFilter::All => true,
+            RemovedFilter::Only => r.removal().is_removed(),
+            RemovedFilter::Hide => !r.removal().is_removed(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RemovedFilter {
+    All,
+    Only,
+    Hide,
+}
+
+static ALL_STR: &str = "Show all";
+static ONLY_STR: &str = "Only removed";
+static HIDE_STR: &str = "Hide removed";
+
+impl RemovedFilter {
+    pub fn list() -> Vec<String> {
+        vec![ALL_STR.into(), ONLY_STR.into(), HIDE_STR.into()]
+    }
+
+    /// Short name used in page URLs
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::Only => "only",
+            Self::Hide => "hide",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "all" => Some(Self::All),
+            "only" => Some(Self::Only),
+            "hide" => Some(Self::Hide),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RemovedFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "{}", ALL_STR),
+            Self::Only => write!(f, "{}", ONLY_STR),
+            Self::Hide => write!(f, "{}", HIDE_STR),
+        }
+    }
+}
+
+impl FromStr for RemovedFilter {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            x if x == ALL_STR => Ok(Self::All),
+            x if x == ONLY_STR => Ok(Self::Only),
+            x if x == HIDE_STR => Ok(Self::Hide),
+            _ => Err(()),
+        }
+    }
+}
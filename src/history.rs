@@ -0,0 +1,154 @@
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::params::SearchParams;
+
+static HISTORY_KEY: &str = "search_history";
+/// Unpinned entries beyond this are dropped, oldest first
+const MAX_ENTRIES: usize = 100;
+
+/// An executed search
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub id: u64,
+    pub params: SearchParams,
+    /// Unix timestamp of when the search was run
+    pub time: i64,
+    pub results: usize,
+    pub backend: String,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub outcome: Outcome,
+}
+
+/// How a recorded search went
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    /// Still running, or left before its first results arrived
+    Running,
+    /// Entries stored before outcomes were recorded only exist for successful searches
+    #[default]
+    Succeeded,
+    Failed(String),
+}
+
+impl HistoryEntry {
+    pub fn title(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.params.summary(),
+        }
+    }
+}
+
+/// Searches run in this browser, persisted in LocalStorage
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SearchHistory {
+    entries: Vec<HistoryEntry>,
+}
+
+impl SearchHistory {
+    pub fn load() -> Self {
+        let entries = LocalStorage::get(HISTORY_KEY).unwrap_or_default();
+        Self { entries }
+    }
+
+    pub fn store(&self) {
+        if let Err(e) = LocalStorage::set(HISTORY_KEY, &self.entries) {
+            log::warn!("Unable to store search history: {}", e);
+        }
+    }
+
+    /// Pinned entries first, then newest first
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        let mut entries = self.entries.clone();
+        entries.sort_by_key(|e| (!e.pinned, -e.time));
+        entries
+    }
+
+    pub fn get(&self, id: u64) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|e| e.id == id)
+    }
+
+    /// Add a search that has just started and return its id
+    pub fn record(&mut self, params: SearchParams, backend: String) -> u64 {
+        let now = js_sys::Date::now();
+        let id = self
+            .entries
+            .iter()
+            .map(|e| e.id + 1)
+            .max()
+            .unwrap_or_default()
+            .max(now as u64);
+        self.entries.push(HistoryEntry {
+            id,
+            params,
+            time: (now / 1000.0) as i64,
+            results: 0,
+            backend,
+            pinned: false,
+            name: None,
+            outcome: Outcome::Running,
+        });
+
+        // Drop the oldest unpinned entries
+        let unpinned = self.entries.iter().filter(|e| !e.pinned).count();
+        let mut excess = unpinned.saturating_sub(MAX_ENTRIES);
+        self.entries.retain(|e| {
+            if excess > 0 && !e.pinned {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+
+        self.store();
+        id
+    }
+
+    pub fn set_results(&mut self, id: u64, results: usize) {
+        self.modify(id, |e| e.results = results);
+    }
+
+    /// Mark a search as done with its first results
+    pub fn succeed(&mut self, id: u64, results: usize) {
+        self.modify(id, |e| {
+            e.results = results;
+            e.outcome = Outcome::Succeeded;
+        });
+    }
+
+    /// Mark a running search as failed, later failures to get more results don't count
+    pub fn fail(&mut self, id: u64, error: &str) {
+        self.modify(id, |e| {
+            if e.outcome == Outcome::Running {
+                e.outcome = Outcome::Failed(error.to_owned());
+            }
+        });
+    }
+
+    pub fn toggle_pinned(&mut self, id: u64) {
+        self.modify(id, |e| e.pinned = !e.pinned);
+    }
+
+    pub fn rename(&mut self, id: u64, name: String) {
+        let name = Some(name.trim().to_owned()).filter(|n| !n.is_empty());
+        self.modify(id, |e| e.name = name);
+    }
+
+    pub fn delete(&mut self, id: u64) {
+        self.entries.retain(|e| e.id != id);
+        self.store();
+    }
+
+    fn modify(&mut self, id: u64, f: impl FnOnce(&mut HistoryEntry)) {
+        if let Some(e) = self.entries.iter_mut().find(|e| e.id == id) {
+            f(e);
+            self.store();
+        }
+    }
+}
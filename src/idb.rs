@@ -0,0 +1,96 @@
+use js_sys::Promise;
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{
+    DomException, IdbDatabase, IdbObjectStoreParameters, IdbOpenDbRequest, IdbRequest,
+    IdbTransaction, IdbVersionChangeEvent,
+};
+
+static DB_NAME: &str = "rgrep";
+const DB_VERSION: u32 = 2;
+/// Items of the local corpus, by fullname
+pub static CORPUS_STORE: &str = "items";
+pub static SUBREDDIT_INDEX: &str = "subreddit";
+pub static AUTHOR_INDEX: &str = "author";
+/// Snapshot of the last session's results
+pub static SESSION_STORE: &str = "session";
+
+pub async fn open() -> Result<IdbDatabase, JsValue> {
+    let factory = gloo_utils::window()
+        .indexed_db()?
+        .ok_or_else(|| JsValue::from("IndexedDB is not available"))?;
+    let request = factory.open_with_u32(DB_NAME, DB_VERSION)?;
+
+    let on_upgrade = {
+        let request = request.clone();
+        Closure::once_into_js(move |e: IdbVersionChangeEvent| upgrade(&request, e.old_version()))
+    };
+    request.set_onupgradeneeded(Some(on_upgrade.unchecked_ref()));
+
+    Ok(wait(&request).await?.unchecked_into())
+}
+
+/// Create the stores added since the version of the existing database
+fn upgrade(request: &IdbOpenDbRequest, old_version: f64) -> Result<(), JsValue> {
+    let db: IdbDatabase = request.result()?.unchecked_into();
+    if old_version < 1.0 {
+        let mut params = IdbObjectStoreParameters::new();
+        params.key_path(Some(&"id".into()));
+        let store = db.create_object_store_with_optional_parameters(CORPUS_STORE, &params)?;
+        store.create_index_with_str(SUBREDDIT_INDEX, "subreddit")?;
+        store.create_index_with_str(AUTHOR_INDEX, "author")?;
+    }
+    if old_version < 2.0 {
+        db.create_object_store(SESSION_STORE)?;
+    }
+    Ok(())
+}
+
+/// Wait for a request to succeed and get its result
+pub async fn wait(request: &IdbRequest) -> Result<JsValue, JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_success = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let result = request.result().unwrap_or(JsValue::UNDEFINED);
+                resolve.call1(&JsValue::NULL, &result)
+            })
+        };
+        let on_error = {
+            let request = request.clone();
+            Closure::once_into_js(move || {
+                let error = request.error().ok().flatten().map(JsValue::from);
+                reject.call1(&JsValue::NULL, &error.unwrap_or(JsValue::NULL))
+            })
+        };
+        request.set_onsuccess(Some(on_success.unchecked_ref()));
+        request.set_onerror(Some(on_error.unchecked_ref()));
+    });
+    JsFuture::from(promise).await
+}
+
+/// Wait for a transaction to be committed
+pub async fn complete(tx: &IdbTransaction) -> Result<(), JsValue> {
+    let promise = Promise::new(&mut |resolve, reject| {
+        let on_complete = Closure::once_into_js(move || resolve.call0(&JsValue::NULL));
+        // Failed requests abort the transaction
+        let on_abort = {
+            let tx = tx.clone();
+            Closure::once_into_js(move || {
+                let error = tx.error().map(JsValue::from);
+                reject.call1(&JsValue::NULL, &error.unwrap_or(JsValue::NULL))
+            })
+        };
+        tx.set_oncomplete(Some(on_complete.unchecked_ref()));
+        tx.set_onabort(Some(on_abort.unchecked_ref()));
+    });
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+pub fn describe(e: JsValue) -> String {
+    match e.dyn_ref::<DomException>() {
+        Some(e) => e.message(),
+        None => e.as_string().unwrap_or_else(|| format!("{:?}", e)),
+    }
+}
@@ -0,0 +1,55 @@
+use serde_json::{json, Value};
+
+use crate::listing;
+use crate::params::Endpoint;
+use crate::pushshift::RedditType;
+
+/// Parse results from an exported JSON or NDJSON file, or a saved API response
+///
+/// Items are parsed like API responses, with runs of the same kind together.
+pub fn parse(text: &str, tz_offset: i64) -> Result<Vec<RedditType>, serde_json::Error> {
+    let items = match serde_json::from_str(text) {
+        Ok(value) if listing::is_reddit_format(&value) => {
+            let mut results = Vec::new();
+            listing::items(value, tz_offset, &mut results)?;
+            return Ok(results);
+        }
+        Ok(Value::Object(mut o)) => match o.remove("data") {
+            Some(Value::Array(items)) => items,
+            _ => vec![Value::Object(o)],
+        },
+        Ok(Value::Array(items)) => items,
+        Ok(item) => vec![item],
+        // One item per line
+        Err(_) => text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?,
+    };
+    parse_values(items, tz_offset)
+}
+
+/// Parse already decoded items
+pub fn parse_values(
+    items: Vec<Value>,
+    tz_offset: i64,
+) -> Result<Vec<RedditType>, serde_json::Error> {
+    let mut results = Vec::with_capacity(items.len());
+    let mut run: Vec<Value> = Vec::new();
+    let mut run_endpoint = Endpoint::Comment;
+    for item in items {
+        let endpoint = Endpoint::detect(&item);
+        if endpoint != run_endpoint && !run.is_empty() {
+            let data = json!({ "data": std::mem::take(&mut run) });
+            results.extend(run_endpoint.parse(data.to_string(), tz_offset)?);
+        }
+        run_endpoint = endpoint;
+        run.push(item);
+    }
+    if !run.is_empty() {
+        let data = json!({ "data": run });
+        results.extend(run_endpoint.parse(data.to_string(), tz_offset)?);
+    }
+    Ok(results)
+}
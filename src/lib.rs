@@ -0,0 +1,23 @@
+pub mod archive;
+pub mod component;
+pub mod corpus;
+pub mod deep_link;
+pub mod diagnostics;
+pub mod download;
+pub mod dump;
+pub mod export;
+pub mod fetch;
+pub mod filter;
+pub mod history;
+pub mod idb;
+pub mod import;
+pub mod listing;
+pub mod live;
+pub mod media;
+pub mod params;
+pub mod presets;
+pub mod pushshift;
+pub mod search_index;
+pub mod session;
+pub mod settings;
+pub mod worker;
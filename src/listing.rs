@@ -0,0 +1,184 @@
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::pushshift::RedditType;
+
+/// Reddit's envelope for a list of things, as returned by its own API
+#[derive(Deserialize)]
+pub struct Listing<T> {
+    pub data: ListingData<T>,
+}
+
+#[derive(Deserialize)]
+pub struct ListingData<T> {
+    pub children: Vec<Thing<T>>,
+}
+
+/// An item of a listing, `kind` is the prefix of its fullname like `t1` for comments
+#[derive(Deserialize)]
+pub struct Thing<T> {
+    pub kind: String,
+    pub data: T,
+}
+
+static COMMENT_KIND: &str = "t1";
+static SUBMISSION_KIND: &str = "t3";
+
+fn is_listing(value: &Value) -> bool {
+    value.get("kind").and_then(Value::as_str) == Some("Listing")
+}
+
+/// Whether `value` is in Reddit's format rather than an archive's
+///
+/// Pages saved from reddit.com are a listing, or an array of the submission's
+/// listing and the comments' listing.
+pub fn is_reddit_format(value: &Value) -> bool {
+    match value {
+        Value::Array(values) => values.first().is_some_and(is_listing),
+        value => is_listing(value) || is_thing(value),
+    }
+}
+
+fn is_thing(value: &Value) -> bool {
+    value.get("kind").is_some_and(Value::is_string)
+        && value.get("data").is_some_and(Value::is_object)
+}
+
+/// Collect the comments and submissions of listings and things, with comment replies
+///
+/// Other kinds, like the `more` placeholders of long comment trees, are skipped.
+pub fn items(
+    value: Value,
+    tz_offset: i64,
+    out: &mut Vec<RedditType>,
+) -> Result<(), serde_json::Error> {
+    match value {
+        Value::Array(values) => {
+            for v in values {
+                items(v, tz_offset, out)?;
+            }
+        }
+        mut value if is_listing(&value) => {
+            if let Some(children) = value.pointer_mut("/data/children") {
+                items(children.take(), tz_offset, out)?;
+            }
+        }
+        value if is_thing(&value) => {
+            let Thing { kind, mut data } = Thing::<Value>::deserialize(value)?;
+            // Replies are a listing, or an empty string when there are none
+            let replies = data.as_object_mut().and_then(|d| d.remove("replies"));
+            match kind.as_str() {
+                k if k == COMMENT_KIND => out.push(RedditType::comment(data, tz_offset)?),
+                k if k == SUBMISSION_KIND => out.push(RedditType::submission(data, tz_offset)?),
+                _ => {}
+            }
+            if let Some(replies) = replies {
+                items(replies, tz_offset, out)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Parse a response or saved page in Reddit's listing format
+pub fn parse(json: impl AsRef<str>, tz_offset: i64) -> Result<Vec<RedditType>, serde_json::Error> {
+    let value: Value = serde_json::from_str(json.as_ref())?;
+    if !is_reddit_format(&value) {
+        return Err(serde::de::Error::custom("not a Reddit listing"));
+    }
+    let mut found = Vec::new();
+    items(value, tz_offset, &mut found)?;
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn comment(id: &str, replies: Value) -> Value {
+        json!({
+            "kind": "t1",
+            "data": {
+                "id": id,
+                "author": "someone",
+                "body": "text",
+                "created_utc": 1600000000.0,
+                "link_id": "t3_abc",
+                "subreddit": "rust",
+                "permalink": format!("/r/rust/comments/abc/title/{}/", id),
+                "replies": replies,
+            },
+        })
+    }
+
+    fn submission(id: &str) -> Value {
+        json!({
+            "kind": "t3",
+            "data": {
+                "id": id,
+                "author": "someone",
+                "title": "A title",
+                "selftext": "text",
+                "is_self": true,
+                "thumbnail": "self",
+                "url": "https://www.reddit.com/r/rust/comments/abc/",
+                "created_utc": 1600000000.0,
+                "subreddit": "rust",
+            },
+        })
+    }
+
+    fn listing(children: Vec<Value>) -> Value {
+        json!({ "kind": "Listing", "data": { "children": children } })
+    }
+
+    fn fullnames(value: Value) -> Vec<String> {
+        parse(value.to_string(), 0)
+            .unwrap()
+            .iter()
+            .map(RedditType::fullname)
+            .collect()
+    }
+
+    #[test]
+    fn listing_of_comments() {
+        let value = listing(vec![comment("c1", json!("")), comment("c2", json!(""))]);
+        assert_eq!(fullnames(value), ["t1_c1", "t1_c2"]);
+    }
+
+    #[test]
+    fn submission_page_with_replies() {
+        let more = json!({ "kind": "more", "data": { "count": 5, "children": ["c9"] } });
+        let reply = comment("c2", json!(""));
+        let value = json!([
+            listing(vec![submission("abc")]),
+            listing(vec![
+                comment("c1", listing(vec![reply, more])),
+                comment("c3", json!(""))
+            ]),
+        ]);
+        assert_eq!(fullnames(value), ["t3_abc", "t1_c1", "t1_c2", "t1_c3"]);
+    }
+
+    #[test]
+    fn mixed_kinds() {
+        let value = listing(vec![
+            comment("c1", json!("")),
+            submission("abc"),
+            comment("c2", json!("")),
+        ]);
+        let items = parse(value.to_string(), 0).unwrap();
+        assert!(matches!(items[0], RedditType::Comment(_)));
+        assert!(matches!(items[1], RedditType::Submission(_)));
+        assert!(matches!(items[2], RedditType::Comment(_)));
+        assert_eq!(items[1].title(), Some("A title"));
+    }
+
+    #[test]
+    fn not_a_listing() {
+        assert!(parse(r#"{"data": []}"#, 0).is_err());
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+use yew::prelude::*;
+
+use crate::listing::Listing;
+use crate::pushshift::{Removal, RemovedBy};
+
+/// Maximum number of fullnames Reddit's `/api/info` accepts per request
+pub const BATCH_SIZE: usize = 100;
+
+/// Diffs of larger texts are skipped to keep the LCS table small
+const MAX_DIFF_WORDS: usize = 2000;
+
+/// Current state of an archived item according to Reddit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LiveState {
+    Found {
+        score: i64,
+        removal: Removal,
+        /// Word diff from the archived text to the live one, if it was edited since
+        edit: Option<Vec<Diff>>,
+    },
+    /// The item was not returned at all
+    Missing,
+}
+
+#[derive(Deserialize)]
+struct LiveItem {
+    name: String,
+    #[serde(default)]
+    score: i64,
+    author: String,
+    body: Option<String>,
+    selftext: Option<String>,
+    #[serde(default)]
+    is_self: bool,
+    removed_by_category: Option<String>,
+}
+
+/// Build the `/api/info` request URL for a batch of fullnames
+pub fn info_url(api: &str, fullnames: &[String]) -> Result<String, url::ParseError> {
+    let mut url = url::Url::parse(api)?;
+    url.query_pairs_mut()
+        .append_pair("id", &fullnames.join(","))
+        .append_pair("raw_json", "1");
+    Ok(url.to_string())
+}
+
+/// Parse an `/api/info` listing, marking requested items that were not returned as missing
+///
+/// `items` are the requested fullnames with the archived text of each, to diff the live text against.
+pub fn parse_info(
+    json: impl AsRef<str>,
+    items: &[(String, String)],
+) -> Result<HashMap<String, LiveState>, serde_json::Error> {
+    let listing: Listing<LiveItem> = serde_json::from_str(json.as_ref())?;
+
+    let archived: HashMap<_, _> = items.iter().map(|(f, t)| (f, t)).collect();
+    let mut states: HashMap<_, _> = items
+        .iter()
+        .map(|(f, _)| (f.clone(), LiveState::Missing))
+        .collect();
+    for thing in listing.data.children {
+        let item = thing.data;
+        let text = match (item.body, item.selftext) {
+            (Some(b), _) => b,
+            (None, Some(s)) if item.is_self => s,
+            _ => String::new(),
+        };
+        let text = html_escape::decode_html_entities(&text).into_owned();
+        let removal = Removal::new(&item.author, &text, item.removed_by_category.as_deref());
+        // Only diff against live text that still exists
+        let edit = match archived.get(&item.name) {
+            Some(old) if status(&removal).0 == "live" && text.trim() != old.trim() => {
+                Some(diff_words(old, &text))
+            }
+            _ => None,
+        };
+        states.insert(
+            item.name,
+            LiveState::Found {
+                score: item.score,
+                removal,
+                edit,
+            },
+        );
+    }
+
+    Ok(states)
+}
+
+/// Status badge text and class of an item found on Reddit
+fn status(removal: &Removal) -> (&'static str, &'static str) {
+    match (&removal.body, removal.category.as_deref()) {
+        (Some(RemovedBy::Author), _) | (None, Some("deleted" | "author")) => {
+            ("deleted by author", "badge_deleted")
+        }
+        (Some(RemovedBy::Moderator), _) | (None, Some(_)) => {
+            ("removed by moderators", "badge_removed")
+        }
+        (None, None) => ("live", "badge_preserved"),
+    }
+}
+
+impl LiveState {
+    pub fn html(&self) -> Html {
+        let (score, removal, edit) = match self {
+            Self::Missing => {
+                return html! {
+                    <div class="live">
+                        <span class="badge badge_lost">{"not found on Reddit"}</span>
+                    </div>
+                }
+            }
+            Self::Found {
+                score,
+                removal,
+                edit,
+            } => (score, removal, edit),
+        };
+        let (status, class) = status(removal);
+
+        let (edited_badge, diff) = match edit {
+            Some(edit) => (
+                html! { <span class="badge badge_edited">{"edited since archived"}</span> },
+                html! {
+                    <div class="comment_body diff">
+                        {diff_html(edit)}
+                    </div>
+                },
+            ),
+            None => (html! {}, html! {}),
+        };
+
+        html! {
+            <div class="live">
+                <div class="badges">
+                    <span class={classes!("badge", class)}>{status}</span>
+                    <span class="badge">{format!("score {}", score)}</span>
+                    {edited_badge}
+                </div>
+                {diff}
+            </div>
+        }
+    }
+}
+
+/// A word of an edited text
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Diff {
+    Same(String),
+    Removed(String),
+    Added(String),
+}
+
+/// Word level diff using the longest common subsequence
+fn diff_words(old: &str, new: &str) -> Vec<Diff> {
+    let old: Vec<_> = old.split_whitespace().collect();
+    let new: Vec<_> = new.split_whitespace().collect();
+
+    if old.len() > MAX_DIFF_WORDS || new.len() > MAX_DIFF_WORDS {
+        let mut diff: Vec<_> = old
+            .into_iter()
+            .map(|w| Diff::Removed(w.to_owned()))
+            .collect();
+        diff.extend(new.into_iter().map(|w| Diff::Added(w.to_owned())));
+        return diff;
+    }
+
+    // lcs[i][j] is the LCS length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0u16; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            diff.push(Diff::Same(old[i].to_owned()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(Diff::Removed(old[i].to_owned()));
+            i += 1;
+        } else {
+            diff.push(Diff::Added(new[j].to_owned()));
+            j += 1;
+        }
+    }
+    diff.extend(old[i..].iter().map(|w| Diff::Removed((*w).to_owned())));
+    diff.extend(new[j..].iter().map(|w| Diff::Added((*w).to_owned())));
+    diff
+}
+
+fn diff_html(diff: &[Diff]) -> Html {
+    html! {
+        {for diff.iter().map(|d| match d {
+            Diff::Same(w) => html! { <span>{w}{" "}</span> },
+            Diff::Removed(w) => html! { <><del>{w}</del>{" "}</> },
+            Diff::Added(w) => html! { <><ins>{w}</ins>{" "}</> },
+        })}
+    }
+}
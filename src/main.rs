@@ -1,19 +1,50 @@
-mod component;
-mod fetch;
-mod params;
-mod pushshift;
-
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
 use std::str::FromStr;
 
-use component::search_box::SearchBox;
-use component::search_button::{SearchButton, SearchState};
-use component::select::Select;
-use component::Width;
-use fetch::fetch;
-use params::{Endpoint, SearchParams};
-use pushshift::RedditType;
-use time::{format_description, PrimitiveDateTime, UtcOffset};
+use gloo_events::EventListener;
+use gloo_timers::future::TimeoutFuture;
+use gloo_worker::{Spawnable, WorkerBridge};
+use pushshift_web::archive::Archive;
+use pushshift_web::component::checkbox::Checkbox;
+use pushshift_web::component::corpus_panel::CorpusPanel;
+use pushshift_web::component::date_time_picker::DateTimePicker;
+use pushshift_web::component::diagnostics_panel::DiagnosticsPanel;
+use pushshift_web::component::export_panel::ExportPanel;
+use pushshift_web::component::file_input::FileInput;
+use pushshift_web::component::history_panel::HistoryPanel;
+use pushshift_web::component::imported_info::{ImportedInfo, ScanStatus};
+use pushshift_web::component::lightbox::Lightbox;
+use pushshift_web::component::presets_editor::PresetsEditor;
+use pushshift_web::component::result_list::ResultList;
+use pushshift_web::component::search_box::SearchBox;
+use pushshift_web::component::search_button::{SearchButton, SearchState};
+use pushshift_web::component::search_info::SearchInfo;
+use pushshift_web::component::select::Select;
+use pushshift_web::component::sentinel::Sentinel;
+use pushshift_web::component::text_input::TextInput;
+use pushshift_web::component::Width;
+use pushshift_web::corpus::{self, Purge, Record};
+use pushshift_web::deep_link::DeepLink;
+use pushshift_web::diagnostics::{self, Diagnostics};
+use pushshift_web::download;
+use pushshift_web::dump::{self, DumpFilter};
+use pushshift_web::export::{Column, ExportFormat, ExportOptions};
+use pushshift_web::fetch::{fetch_with_status, FetchError};
+use pushshift_web::filter::{Filters, RemovedFilter};
+use pushshift_web::history::SearchHistory;
+use pushshift_web::live::{self, LiveState};
+use pushshift_web::media::{Media, OnExpand};
+use pushshift_web::params::{parse_time, Endpoint, SearchParams};
+use pushshift_web::presets::{Preset, Presets, NO_PRESET};
+use pushshift_web::pushshift::{format_timestamp, Metadata, Page, RedditType};
+use pushshift_web::search_index::SearchIndex;
+use pushshift_web::session::{self, Session};
+use pushshift_web::settings::{SensitiveMedia, Settings};
+use pushshift_web::worker::{ParseRequest, ParseResponse, ParseWorker, WORKER_PATH};
 use url::Url;
+use wasm_bindgen::JsCast;
+use web_sys::{File, Notification, NotificationOptions, NotificationPermission, PopStateEvent};
 use yew::prelude::*;
 
 #[derive(Debug)]
@@ -25,63 +56,676 @@ pub enum FetchState {
     Failed(String),
 }
 
+/// Number of searches whose results are kept for back and forward navigation
+const HISTORY_CACHE_SIZE: usize = 10;
+/// Delay before infinite scroll fetches again after a page was appended
+const AUTO_MORE_DELAY_MS: u32 = 250;
+/// Bounds of the backoff after failed fetches in infinite scroll mode
+const BACKOFF_MIN_MS: u32 = 1000;
+const BACKOFF_MAX_MS: u32 = 60000;
+/// Longest wait between checks of a watched search that keeps failing
+const WATCH_BACKOFF_MAX_MS: u32 = 30 * 60 * 1000;
+/// Delay before saving the session after results changed, to save once for a burst of changes
+const SESSION_SAVE_DELAY_MS: u32 = 2000;
+
+#[derive(Debug)]
+pub enum EnrichState {
+    Idle,
+    Working { done: usize, total: usize },
+    Failed(String),
+}
+
+/// Results of a search, kept to restore them when navigating through history
+struct CachedSearch {
+    id: u32,
+    params: SearchParams,
+    results: Vec<Rc<RedditType>>,
+    exhausted: bool,
+    queries: Vec<(String, i64)>,
+    local: bool,
+    metadata: Option<Metadata>,
+}
+
+/// Polling of the current search for new results
+struct Watch {
+    // Distinguishes timers of this watch from ones of a stopped watch
+    generation: u32,
+    failures: u32,
+    error: Option<String>,
+}
+
+/// A dump file being searched
+struct DumpScan {
+    // Worker job of this scan, to ignore messages of a cancelled scan
+    id: u32,
+    /// Object URL the worker reads the file from
+    url: String,
+    status: ScanStatus,
+}
+
+/// What to do with results parsed by the worker
+enum Job {
+    Search(SearchType, SearchParams, Endpoint),
+    Import(String),
+    /// Search of the local corpus
+    CorpusSearch(SearchParams),
+    /// Adding the local corpus to the full-text index
+    CorpusIndex,
+    /// Bringing back the last session, its results taken out
+    Restore(Session),
+    /// Live state of a batch of results, by fullname and archived text
+    Enrich(Vec<(String, String)>),
+}
+
 #[derive(Debug)]
 enum Msg {
     Search,
+    /// Browser navigated to the history entry with the given id
+    PopState(Option<u32>),
     More,
+    AutoMore,
+    SentinelVisible(bool),
+    Enrich,
+    ToggleWatch,
+    /// Time to check the watch with the given generation for new results
+    Poll(u32),
+    Polled((u32, Result<Vec<RedditType>, String>)),
+    /// An API response was received for the job with the given id, with its latency
+    Fetched((u32, f64, Result<(u16, String), FetchError>)),
+    Parsed((u32, Result<Page, String>)),
+    /// The tab became visible, so new results have been seen
+    MarkRead,
+    /// The tab was hidden and may not come back
+    PageHidden,
+    SessionLoaded(Result<Option<Session>, String>),
+    RestoreSession,
+    DismissSession,
+    /// Save the session if no newer save was scheduled
+    SaveSession(u32),
+    SessionSaved(Result<(), String>),
+    RestoreScroll(f64),
     SetPsFetchState(FetchState),
+    SetLiveState(Result<HashMap<String, LiveState>, String>),
+    Expand((Vec<Media>, usize)),
+    CloseLightbox,
+    RunHistory(u64),
+    TogglePinned(u64),
+    RenameHistory((u64, String)),
+    DeleteHistory(u64),
+    CopyRequest(u32),
+    OpenResponse(u32),
+    SelectPreset(String),
+    UpdatePresetName(String),
+    SavePreset,
+    DeletePreset,
+    ExportPresets,
+    ImportPresets(File),
+    PresetsImported(Result<String, String>),
     UpdateEndpoint(String),
     UpdateSubreddit(String),
     UpdateAuthor(String),
     UpdateQuery(String),
     UpdateTimeStart(String),
     UpdateTimeEnd(String),
+    UpdateRemovedFilter(String),
+    UpdateLiveApi(String),
+    UpdateSensitiveMedia(String),
+    UpdateVirtualize(bool),
+    UpdateInfiniteScroll(bool),
+    UpdateWatchInterval(String),
+    UpdateWatchNotify(bool),
+    UpdateCorpus(bool),
+    UpdateCorpusOnly(bool),
+    CorpusStored(Result<(), String>),
+    RefreshCorpusStats,
+    CorpusStats(Result<corpus::Stats, String>),
+    PurgeCorpus(Purge),
+    CorpusPurged(Result<usize, String>),
+    UpdateIndexQuery(String),
+    /// Stored items that may match the corpus search of the worker job with the given id
+    CorpusRead((u32, Result<String, String>)),
+    CorpusLoaded(Result<String, String>),
+    UpdateExportFormat(String),
+    UpdateExportColumn((Column, bool)),
+    Export,
+    ExportArchive,
+    ImportResults(File),
+    ResultsRead((String, Result<String, String>)),
+    SearchDump(File),
+    DumpProgress((u32, dump::Progress)),
+    DumpDone((u32, Result<bool, String>)),
+    CancelDump,
 }
 
 struct Model {
-    results: Vec<RedditType>,
+    results: Vec<Rc<RedditType>>,
+    /// Changes whenever the results are replaced by a different list
+    results_generation: u32,
     state: FetchState,
     tz_offset: i64,
     params: SearchParams,
+    filters: Filters,
+    settings: Settings,
     // For use when "more-ing"
     last_params: Option<SearchParams>,
+    // URLs fetched for the current results and when
+    queries: Vec<(String, i64)>,
+    // Last "more" page came back empty
+    exhausted: bool,
+    // Infinite scroll state
+    sentinel_visible: bool,
+    failures: u32,
+    backoff_until: f64,
+    // Live Reddit state keyed by fullname
+    live: Rc<HashMap<String, Rc<LiveState>>>,
+    enrich: EnrichState,
+    /// Batches of fullnames and archived texts still to check the live state of, last first
+    enrich_queue: Vec<Vec<(String, String)>>,
+    // Media shown in the lightbox and index to start at
+    lightbox: Option<(Vec<Media>, usize)>,
+    // Created once so result cards see unchanged props
+    on_expand: OnExpand,
+    // Id of the current history entry, and of the next one to push
+    history_id: u32,
+    next_history_id: u32,
+    history_cache: Vec<CachedSearch>,
+    _popstate: EventListener,
+    // Persistent search history, and the entry of the current results
+    history: SearchHistory,
+    history_entry: Option<u64>,
+    // Saved searches, the selected one and the name to save the form under
+    presets: Presets,
+    selected_preset: String,
+    preset_name: String,
+    presets_status: Option<String>,
+    // Watched search, results found by it and how many arrived while hidden
+    watch: Option<Watch>,
+    watch_generation: u32,
+    new_items: Rc<HashSet<String>>,
+    unread: usize,
+    title: String,
+    _visibility: EventListener,
+    export: ExportOptions,
+    export_error: Option<String>,
+    // Name of the file the results were loaded from
+    imported: Option<String>,
+    import_error: Option<String>,
+    dump: Option<DumpScan>,
+    /// Current results came from the local corpus, so there is nothing more to fetch
+    local: bool,
+    /// What the API told about the current search, with the total counted from its start
+    metadata: Option<Metadata>,
+    corpus_stats: Option<corpus::Stats>,
+    corpus_status: Option<String>,
+    /// Full-text index of everything collected in this session
+    index: SearchIndex,
+    index_query: String,
+    /// Results of the index query, shown instead of the search's results
+    index_hits: Option<Rc<Vec<Rc<RedditType>>>>,
+    /// Whether the local corpus was added to the index
+    corpus_indexed: bool,
+    /// Snapshot of the last session, until it is restored or dismissed
+    restorable: Option<Session>,
+    session_generation: u32,
+    /// Parses responses and files off the UI thread
+    worker: WorkerBridge<ParseWorker>,
+    jobs: HashMap<u32, Job>,
+    next_job: u32,
+    /// API requests of this session
+    diagnostics: Diagnostics,
 }
 
 #[derive(Clone, Debug)]
 pub enum SearchType {
     Initial,
     More,
+    /// A poll of the watch with the given generation
+    Watch(u32),
 }
 
 impl Component for Model {
     type Message = Msg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
+    fn create(ctx: &Context<Self>) -> Self {
         // Get current timezone offset
         let tz_offset = -js_sys::Date::new_0().get_timezone_offset() as i64;
 
+        // A search in the page URL takes precedence over the last used one
+        let deep_link = DeepLink::from_location(tz_offset);
+        let (params, filters) = match &deep_link {
+            Some(link) => (link.params.clone(), link.filters.clone()),
+            None => (SearchParams::load(), Filters::load()),
+        };
+        if deep_link.is_some_and(|link| link.run) {
+            ctx.link().send_message(Msg::PopState(None));
+        } else {
+            ctx.link()
+                .send_future(async { Msg::SessionLoaded(session::load().await) });
+        }
+
+        let popstate = {
+            let link = ctx.link().clone();
+            EventListener::new(&gloo_utils::window(), "popstate", move |e| {
+                let id = e
+                    .dyn_ref::<PopStateEvent>()
+                    .and_then(|e| e.state().as_f64())
+                    .map(|id| id as u32);
+                link.send_message(Msg::PopState(id));
+            })
+        };
+
+        let visibility = {
+            let link = ctx.link().clone();
+            EventListener::new(&gloo_utils::document(), "visibilitychange", move |_| {
+                if gloo_utils::document().hidden() {
+                    link.send_message(Msg::PageHidden);
+                } else {
+                    link.send_message(Msg::MarkRead);
+                }
+            })
+        };
+
+        let worker = {
+            let link = ctx.link().clone();
+            ParseWorker::spawner()
+                .callback(move |response| {
+                    link.send_message(match response {
+                        ParseResponse::Parsed { id, result } => Msg::Parsed((id, result)),
+                        ParseResponse::DumpProgress { id, progress } => {
+                            Msg::DumpProgress((id, progress))
+                        }
+                        ParseResponse::DumpDone { id, result } => Msg::DumpDone((id, result)),
+                    })
+                })
+                .spawn(WORKER_PATH)
+        };
+
         // Create model
         Self {
             results: Vec::new(),
+            results_generation: 0,
             state: FetchState::NotFetching,
             tz_offset,
-            params: SearchParams::load(),
+            params,
+            filters,
+            settings: Settings::load(),
             last_params: None,
+            queries: Vec::new(),
+            exhausted: false,
+            sentinel_visible: false,
+            failures: 0,
+            backoff_until: 0.0,
+            live: Rc::new(HashMap::new()),
+            enrich: EnrichState::Idle,
+            enrich_queue: Vec::new(),
+            lightbox: None,
+            on_expand: ctx.link().callback(Msg::Expand),
+            history_id: 0,
+            next_history_id: 1,
+            history_cache: Vec::new(),
+            _popstate: popstate,
+            history: SearchHistory::load(),
+            history_entry: None,
+            presets: Presets::load(),
+            selected_preset: String::new(),
+            preset_name: String::new(),
+            presets_status: None,
+            watch: None,
+            watch_generation: 0,
+            new_items: Rc::new(HashSet::new()),
+            unread: 0,
+            title: gloo_utils::document().title(),
+            _visibility: visibility,
+            export: ExportOptions::load(),
+            export_error: None,
+            imported: None,
+            import_error: None,
+            dump: None,
+            local: false,
+            metadata: None,
+            corpus_stats: None,
+            corpus_status: None,
+            index: SearchIndex::new(),
+            index_query: String::new(),
+            index_hits: None,
+            corpus_indexed: false,
+            restorable: None,
+            session_generation: 0,
+            worker,
+            jobs: HashMap::new(),
+            next_job: 0,
+            diagnostics: Diagnostics::default(),
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
         match msg {
             Msg::Search => {
-                self.results.clear();
-                self.search(ctx, SearchType::Initial);
+                self.history_id = self.next_history_id;
+                self.next_history_id += 1;
+                self.deep_link(&self.params)
+                    .push_location(self.tz_offset, self.history_id);
+                self.new_search(ctx);
                 false
             }
+            Msg::PopState(id) => {
+                self.stop_watch();
+                self.stop_dump();
+                self.cancel_searches();
+                self.imported = None;
+                self.history_id = id.unwrap_or(0);
+                self.next_history_id = self.next_history_id.max(self.history_id + 1);
+                self.results_generation += 1;
+
+                let link = match DeepLink::from_location(self.tz_offset) {
+                    Some(link) => link,
+                    None => {
+                        // Back at the page without a search
+                        self.results.clear();
+                        self.last_params = None;
+                        self.state = FetchState::NotFetching;
+                        return true;
+                    }
+                };
+                self.params = link.params;
+                self.filters = link.filters;
+                if !link.run {
+                    // Only fill in the form
+                    self.results.clear();
+                    self.last_params = None;
+                    self.state = FetchState::NotFetching;
+                    return true;
+                }
+
+                let cached = self.history_cache.iter().find(|c| c.id == self.history_id);
+                match cached {
+                    Some(cached) => {
+                        self.results = cached.results.clone();
+                        self.last_params = Some(cached.params.clone());
+                        self.exhausted = cached.exhausted;
+                        self.queries = cached.queries.clone();
+                        self.local = cached.local;
+                        self.metadata = cached.metadata.clone();
+                        self.state = FetchState::Done;
+                    }
+                    None => {
+                        // Give the entry an id so its results can be cached
+                        if id.is_none() {
+                            self.history_id = self.next_history_id;
+                            self.next_history_id += 1;
+                        }
+                        self.deep_link(&self.params)
+                            .replace_location(self.tz_offset, self.history_id);
+                        self.new_search(ctx);
+                    }
+                }
+                true
+            }
             Msg::More => {
                 self.search(ctx, SearchType::More);
                 false
             }
+            Msg::AutoMore => {
+                let ready = self.settings.infinite_scroll
+                    && self.sentinel_visible
+                    && !self.exhausted
+                    && !self.results.is_empty()
+                    && self.last_params.is_some()
+                    && !matches!(self.state, FetchState::Fetching)
+                    && js_sys::Date::now() >= self.backoff_until;
+                if ready {
+                    self.search(ctx, SearchType::More);
+                }
+                false
+            }
+            Msg::SentinelVisible(visible) => {
+                self.sentinel_visible = visible;
+                if visible {
+                    ctx.link().send_message(Msg::AutoMore);
+                }
+                false
+            }
+            Msg::Enrich => {
+                self.enrich(ctx);
+                true
+            }
+            Msg::ToggleWatch => {
+                if self.watch.is_some() {
+                    self.stop_watch();
+                } else {
+                    self.watch_generation += 1;
+                    self.watch = Some(Watch {
+                        generation: self.watch_generation,
+                        failures: 0,
+                        error: None,
+                    });
+                    if self.settings.watch_notify {
+                        let _ = Notification::request_permission();
+                    }
+                    ctx.link().send_message(Msg::Poll(self.watch_generation));
+                }
+                true
+            }
+            Msg::Poll(generation) => {
+                match &self.watch {
+                    Some(w) if w.generation == generation => {}
+                    _ => return false,
+                }
+                // Don't interfere with a page being fetched
+                if matches!(self.state, FetchState::Fetching) {
+                    schedule(ctx, AUTO_MORE_DELAY_MS, Msg::Poll(generation));
+                } else {
+                    self.search(ctx, SearchType::Watch(generation));
+                }
+                false
+            }
+            Msg::Fetched((id, latency_ms, result)) => {
+                self.diagnostics.received(id, latency_ms, &result);
+                let result = result.map(|(_, text)| text).map_err(|e| e.to_string());
+                match (result, self.jobs.get(&id)) {
+                    (Ok(json), Some(Job::Search(_, _, endpoint))) => {
+                        self.worker.send(ParseRequest::Parse {
+                            id,
+                            endpoint: endpoint.clone(),
+                            json,
+                            tz_offset: self.tz_offset,
+                        });
+                    }
+                    (result, Some(Job::Enrich(batch))) => {
+                        // Few items per batch, so not worth the worker
+                        let result = result.and_then(|json| {
+                            live::parse_info(json, batch).map_err(|e| e.to_string())
+                        });
+                        let parsed = match &result {
+                            Ok(states) => Ok((states.len(), Vec::new())),
+                            Err(e) => Err(e.as_str()),
+                        };
+                        self.diagnostics.parsed(id, parsed);
+                        self.jobs.remove(&id);
+                        ctx.link().send_message(Msg::SetLiveState(result));
+                    }
+                    (result, _) => {
+                        ctx.link()
+                            .send_message(Msg::Parsed((id, result.map(|_| Page::default()))));
+                    }
+                }
+                false
+            }
+            Msg::Parsed((id, result)) => {
+                let job = self.jobs.remove(&id);
+                if let Some(Job::Search(..)) = &job {
+                    let parsed = match &result {
+                        Ok(page) => {
+                            self.add_to_corpus(ctx, &page.items);
+                            let warnings = page.metadata.iter().flat_map(|m| m.warnings());
+                            let warnings = warnings.chain(page.leniency.warnings());
+                            Ok((page.items.len(), warnings.collect()))
+                        }
+                        Err(e) => Err(e.as_str()),
+                    };
+                    self.diagnostics.parsed(id, parsed);
+                }
+                let result = match result {
+                    Ok(page) => {
+                        if let Some(Job::Search(search_type, _, _)) = &job {
+                            self.update_metadata(search_type, page.metadata);
+                        }
+                        Ok(page.items)
+                    }
+                    Err(e) => Err(e),
+                };
+                match job {
+                    // Polls don't touch the state of the search itself
+                    Some(Job::Search(SearchType::Watch(generation), _, _)) => {
+                        ctx.link().send_message(Msg::Polled((generation, result)));
+                    }
+                    Some(Job::Search(search_type, params, _)) => {
+                        let state = match result {
+                            Ok(p) => FetchState::Success(p, search_type, params),
+                            Err(e) => FetchState::Failed(e),
+                        };
+                        ctx.link().send_message(Msg::SetPsFetchState(state));
+                    }
+                    Some(Job::CorpusSearch(params)) => {
+                        let state = match result {
+                            Ok(mut results) => {
+                                // Newest first, like the API
+                                results.sort_by_key(|r| std::cmp::Reverse(r.time()));
+                                FetchState::Success(results, SearchType::Initial, params)
+                            }
+                            Err(e) => FetchState::Failed(format!(
+                                "Searching the local corpus failed: {}",
+                                e
+                            )),
+                        };
+                        ctx.link().send_message(Msg::SetPsFetchState(state));
+                    }
+                    Some(Job::CorpusIndex) => match result {
+                        Ok(items) => {
+                            for item in items {
+                                self.index.add(Rc::new(item));
+                            }
+                            self.refresh_hits();
+                            return true;
+                        }
+                        Err(e) => {
+                            self.corpus_status = Some(format!("Reading the corpus failed: {}", e));
+                            return true;
+                        }
+                    },
+                    Some(Job::Restore(session)) => match result {
+                        Ok(results) => {
+                            self.restore_session(ctx, session, results);
+                            return true;
+                        }
+                        Err(e) => {
+                            self.import_error = Some(format!("Unable to restore results: {}", e));
+                            return true;
+                        }
+                    },
+                    Some(Job::Import(name)) => match result {
+                        Ok(results) => {
+                            self.show_file(name);
+                            self.results = results.into_iter().map(Rc::new).collect();
+                            self.index_results(0);
+                            return true;
+                        }
+                        Err(e) => {
+                            self.import_error = Some(format!("Unable to open {}: {}", name, e));
+                            return true;
+                        }
+                    },
+                    Some(Job::Enrich(_)) | None => {}
+                }
+                false
+            }
+            Msg::Polled((generation, result)) => {
+                // Results of a watch that was stopped since are dropped
+                let failures = match &self.watch {
+                    Some(w) if w.generation == generation => w.failures,
+                    _ => return false,
+                };
+                let interval = self.settings.watch_interval_ms();
+                let (delay, failures, error) = match result {
+                    Ok(items) => {
+                        self.add_new_items(ctx, items);
+                        (interval, 0, None)
+                    }
+                    Err(e) => {
+                        let delay = interval
+                            .saturating_mul(1 << failures.min(16))
+                            .min(WATCH_BACKOFF_MAX_MS.max(interval));
+                        let error = format!("{} (retrying in {} s)", e, delay / 1000);
+                        (delay, failures + 1, Some(error))
+                    }
+                };
+                self.watch = Some(Watch {
+                    generation,
+                    failures,
+                    error,
+                });
+                schedule(ctx, delay, Msg::Poll(generation));
+                true
+            }
+            Msg::MarkRead => {
+                self.unread = 0;
+                self.update_title();
+                false
+            }
+            Msg::PageHidden => {
+                self.save_session(ctx);
+                false
+            }
+            Msg::SessionLoaded(result) => match result {
+                Ok(Some(session)) if !session.is_empty() && self.results.is_empty() => {
+                    self.restorable = Some(session);
+                    true
+                }
+                Ok(_) => false,
+                Err(e) => {
+                    log::warn!("Unable to load the last session: {}", e);
+                    false
+                }
+            },
+            Msg::RestoreSession => {
+                let mut session = match self.restorable.take() {
+                    Some(s) => s,
+                    None => return false,
+                };
+                self.cancel_searches();
+                let text = session.take_results();
+                let id = self.add_job(Job::Restore(session));
+                self.worker.send(ParseRequest::Import {
+                    id,
+                    text,
+                    filter: None,
+                    tz_offset: self.tz_offset,
+                });
+                true
+            }
+            Msg::DismissSession => {
+                self.restorable = None;
+                ctx.link()
+                    .send_future(async { Msg::SessionSaved(session::clear().await) });
+                true
+            }
+            Msg::SaveSession(generation) => {
+                if generation == self.session_generation {
+                    self.save_session(ctx);
+                }
+                false
+            }
+            Msg::SessionSaved(result) => {
+                if let Err(e) = result {
+                    log::warn!("Unable to save the session: {}", e);
+                }
+                false
+            }
+            Msg::RestoreScroll(y) => {
+                gloo_utils::window().scroll_to_with_x_and_y(0.0, y);
+                false
+            }
             Msg::UpdateEndpoint(s) => {
                 if let Ok(e) = Endpoint::from_str(&s) {
                     self.params.endpoint = e;
@@ -102,12 +746,304 @@ impl Component for Model {
             }
             Msg::UpdateTimeStart(s) => {
                 self.params.time_start = s;
-                false
+                // The pickers show the value and limit each other
+                true
             }
             Msg::UpdateTimeEnd(s) => {
                 self.params.time_end = s;
+                true
+            }
+            Msg::UpdateLiveApi(s) => {
+                self.settings.live_api = s;
+                self.settings.store();
+                false
+            }
+            Msg::UpdateSensitiveMedia(s) => {
+                if let Ok(m) = SensitiveMedia::from_str(&s) {
+                    self.settings.sensitive_media = m;
+                    self.settings.store();
+                }
+                true
+            }
+            Msg::UpdateVirtualize(b) => {
+                self.settings.virtualize = b;
+                self.settings.store();
+                true
+            }
+            Msg::UpdateInfiniteScroll(b) => {
+                self.settings.infinite_scroll = b;
+                self.settings.store();
+                true
+            }
+            Msg::UpdateWatchInterval(s) => {
+                if let Ok(n) = s.trim().parse::<u32>() {
+                    self.settings.watch_interval = n;
+                    self.settings.store();
+                }
+                false
+            }
+            Msg::UpdateCorpus(b) => {
+                self.settings.corpus = b;
+                self.settings.store();
+                if b {
+                    ctx.link().send_message(Msg::RefreshCorpusStats);
+                }
+                true
+            }
+            Msg::UpdateCorpusOnly(b) => {
+                self.settings.corpus_only = b;
+                self.settings.store();
+                true
+            }
+            Msg::CorpusStored(result) => {
+                // The size shown is out of date now
+                self.corpus_stats = None;
+                if let Err(e) = result {
+                    self.corpus_status = Some(format!("Adding results failed: {}", e));
+                }
+                true
+            }
+            Msg::RefreshCorpusStats => {
+                ctx.link()
+                    .send_future(async { Msg::CorpusStats(corpus::stats().await) });
+                false
+            }
+            Msg::CorpusStats(result) => {
+                match result {
+                    Ok(stats) => self.corpus_stats = Some(stats),
+                    Err(e) => {
+                        self.corpus_status = Some(format!("Reading the corpus failed: {}", e))
+                    }
+                }
+                true
+            }
+            Msg::PurgeCorpus(purge) => {
+                let confirmed = match &purge {
+                    Purge::All => gloo_utils::window()
+                        .confirm_with_message("Remove everything from the local corpus?")
+                        .unwrap_or(false),
+                    Purge::Subreddit(s) | Purge::Author(s) => !s.trim().is_empty(),
+                };
+                if confirmed {
+                    ctx.link()
+                        .send_future(async { Msg::CorpusPurged(corpus::purge(purge).await) });
+                }
+                false
+            }
+            Msg::CorpusPurged(result) => {
+                self.corpus_status = Some(match result {
+                    Ok(n) => format!("Removed {} items", n),
+                    Err(e) => format!("Removing items failed: {}", e),
+                });
+                ctx.link().send_message(Msg::RefreshCorpusStats);
+                true
+            }
+            Msg::UpdateIndexQuery(s) => {
+                self.index_query = s;
+                if self.settings.corpus && !self.corpus_indexed {
+                    self.corpus_indexed = true;
+                    ctx.link()
+                        .send_future(async { Msg::CorpusLoaded(corpus::load().await) });
+                }
+                self.refresh_hits();
+                true
+            }
+            Msg::CorpusLoaded(result) => match result {
+                Ok(text) => {
+                    let id = self.add_job(Job::CorpusIndex);
+                    self.worker.send(ParseRequest::Import {
+                        id,
+                        text,
+                        filter: None,
+                        tz_offset: self.tz_offset,
+                    });
+                    false
+                }
+                Err(e) => {
+                    self.corpus_status = Some(format!("Reading the corpus failed: {}", e));
+                    true
+                }
+            },
+            Msg::CorpusRead((id, result)) => {
+                // A newer search replaced this one
+                let params = match self.jobs.get(&id) {
+                    Some(Job::CorpusSearch(params)) => params,
+                    _ => return false,
+                };
+                match result {
+                    Ok(text) => {
+                        let filter = DumpFilter::new(params, self.tz_offset);
+                        self.worker.send(ParseRequest::Import {
+                            id,
+                            text,
+                            filter: Some(filter),
+                            tz_offset: self.tz_offset,
+                        });
+                    }
+                    Err(e) => {
+                        self.jobs.remove(&id);
+                        let error = format!("Searching the local corpus failed: {}", e);
+                        ctx.link()
+                            .send_message(Msg::SetPsFetchState(FetchState::Failed(error)));
+                    }
+                }
+                false
+            }
+            Msg::UpdateWatchNotify(b) => {
+                self.settings.watch_notify = b;
+                self.settings.store();
+                if b {
+                    let _ = Notification::request_permission();
+                }
+                false
+            }
+            Msg::UpdateExportFormat(s) => {
+                if let Ok(f) = ExportFormat::from_str(&s) {
+                    self.export.format = f;
+                    self.export.store();
+                }
+                true
+            }
+            Msg::UpdateExportColumn((column, enabled)) => {
+                self.export.set_column(column, enabled);
+                self.export.store();
+                true
+            }
+            Msg::Export => {
+                let contents = self.export.export(&self.filtered_results());
+                let result = download::download(
+                    &self.export.filename(),
+                    self.export.format.mime(),
+                    &contents,
+                );
+                self.export_error = result.err().map(|e| format!("Export failed: {:?}", e));
+                true
+            }
+            Msg::ExportArchive => {
+                let archive = Archive {
+                    params: self
+                        .last_params
+                        .clone()
+                        .unwrap_or_else(|| self.params.clone()),
+                    filters: self.filters.clone(),
+                    queries: self.queries.clone(),
+                    items: self.filtered_results(),
+                    live: self.live.clone(),
+                };
+                let result =
+                    download::download(&archive.filename(), "text/html", &archive.render());
+                self.export_error = result.err().map(|e| format!("Export failed: {:?}", e));
+                true
+            }
+            Msg::ImportResults(file) => {
+                let name = file.name();
+                ctx.link().send_future(async move {
+                    let text = gloo_file::futures::read_as_text(&file.into()).await;
+                    Msg::ResultsRead((name, text.map_err(|e| e.to_string())))
+                });
+                false
+            }
+            Msg::ResultsRead((name, text)) => {
+                match text {
+                    Ok(text) => {
+                        let id = self.add_job(Job::Import(name));
+                        self.worker.send(ParseRequest::Import {
+                            id,
+                            text,
+                            filter: None,
+                            tz_offset: self.tz_offset,
+                        });
+                    }
+                    Err(e) => {
+                        self.import_error = Some(format!("Unable to open {}: {}", name, e));
+                    }
+                }
+                true
+            }
+            Msg::SearchDump(file) => {
+                let url = match web_sys::Url::create_object_url_with_blob(&file) {
+                    Ok(url) => url,
+                    Err(e) => {
+                        self.import_error =
+                            Some(format!("Unable to open {}: {:?}", file.name(), e));
+                        return true;
+                    }
+                };
+                self.show_file(file.name());
+                let id = self.next_job;
+                self.next_job += 1;
+                self.dump = Some(DumpScan {
+                    id,
+                    url: url.clone(),
+                    status: ScanStatus {
+                        total: file.size() as u64,
+                        running: true,
+                        ..ScanStatus::default()
+                    },
+                });
+                self.worker.send(ParseRequest::ScanDump {
+                    id,
+                    url,
+                    filter: DumpFilter::new(&self.params, self.tz_offset),
+                    tz_offset: self.tz_offset,
+                });
+                true
+            }
+            Msg::DumpProgress((id, progress)) => {
+                let scan = match &mut self.dump {
+                    Some(d) if d.id == id => d,
+                    _ => return false,
+                };
+                scan.status.read = progress.read;
+                scan.status.total = progress.total;
+                scan.status.skipped += progress.skipped;
+                let start = self.results.len();
+                self.results
+                    .extend(progress.matches.into_iter().map(Rc::new));
+                self.index_results(start);
+                true
+            }
+            Msg::DumpDone((id, result)) => {
+                let scan = match &mut self.dump {
+                    Some(d) if d.id == id => d,
+                    _ => return false,
+                };
+                scan.status.running = false;
+                revoke_object_url(&scan.url);
+                match result {
+                    Ok(true) if self.results.len() >= dump::MAX_MATCHES => {
+                        scan.status.note =
+                            Some(format!("stopped after {} matches", dump::MAX_MATCHES));
+                    }
+                    Ok(true) => scan.status.note = Some("cancelled".to_owned()),
+                    Ok(false) => {}
+                    Err(e) => {
+                        self.import_error = Some(format!(
+                            "Searching {} failed: {}",
+                            self.imported.clone().unwrap_or_default(),
+                            e
+                        ));
+                    }
+                }
+                true
+            }
+            Msg::CancelDump => {
+                if let Some(scan) = &self.dump {
+                    self.worker.send(ParseRequest::CancelScan { id: scan.id });
+                }
                 false
             }
+            Msg::UpdateRemovedFilter(s) => {
+                if let Ok(r) = RemovedFilter::from_str(&s) {
+                    self.filters.removed = r;
+                    self.filters.store();
+                }
+                if let Some(params) = &self.last_params {
+                    self.deep_link(params)
+                        .replace_location(self.tz_offset, self.history_id);
+                }
+                true
+            }
             Msg::SetPsFetchState(x) => {
                 self.params.store();
 
@@ -117,42 +1053,262 @@ impl Component for Model {
                 }
 
                 match x {
-                    FetchState::Success(r, SearchType::Initial, _) => {
-                        self.results = r;
+                    FetchState::Success(r, search_type, _) => {
+                        self.exhausted = r.is_empty() || self.local;
+                        self.failures = 0;
+                        self.backoff_until = 0.0;
+                        match search_type {
+                            SearchType::Initial => {
+                                self.results = r.into_iter().map(Rc::new).collect();
+                                self.index_results(0);
+                                if let Some(id) = self.history_entry {
+                                    self.history.succeed(id, self.results.len());
+                                }
+                            }
+                            SearchType::More | SearchType::Watch(_) => {
+                                let start = self.results.len();
+                                self.results.extend(r.into_iter().map(Rc::new));
+                                self.index_results(start);
+                                if let Some(id) = self.history_entry {
+                                    self.history.set_results(id, self.results.len());
+                                }
+                            }
+                        }
                         self.state = FetchState::Done;
+                        self.cache_results();
+                        self.schedule_save(ctx);
+
+                        // Keep going if the sentinel is still on screen after rendering
+                        schedule(ctx, AUTO_MORE_DELAY_MS, Msg::AutoMore);
                     }
-                    FetchState::Success(mut r, SearchType::More, _) => {
-                        self.results.append(&mut r);
-                        self.state = FetchState::Done;
+                    FetchState::Failed(ref error) => {
+                        if let Some(id) = self.history_entry {
+                            self.history.fail(id, error);
+                        }
+                        if self.settings.infinite_scroll {
+                            let delay = BACKOFF_MIN_MS
+                                .saturating_mul(1 << self.failures.min(16))
+                                .min(BACKOFF_MAX_MS);
+                            self.failures += 1;
+                            self.backoff_until = js_sys::Date::now() + delay as f64;
+                            schedule(ctx, delay, Msg::AutoMore);
+                        }
+                        self.state = x;
                     }
                     _ => self.state = x,
                 }
                 true
             }
+            Msg::RunHistory(id) => {
+                if let Some(entry) = self.history.get(id) {
+                    self.params = entry.params.clone();
+                    ctx.link().send_message(Msg::Search);
+                }
+                true
+            }
+            Msg::TogglePinned(id) => {
+                self.history.toggle_pinned(id);
+                true
+            }
+            Msg::RenameHistory((id, name)) => {
+                self.history.rename(id, name);
+                true
+            }
+            Msg::DeleteHistory(id) => {
+                self.history.delete(id);
+                true
+            }
+            Msg::CopyRequest(id) => {
+                if let Some(entry) = self.diagnostics.get(id) {
+                    let curl = entry.curl();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Err(e) = diagnostics::copy(&curl).await {
+                            log::warn!("Unable to copy to the clipboard: {:?}", e);
+                        }
+                    });
+                }
+                false
+            }
+            Msg::OpenResponse(id) => {
+                if let Some(body) = self.diagnostics.body(id) {
+                    if let Err(e) = download::open("application/json", &body) {
+                        log::warn!("Unable to open the response: {:?}", e);
+                    }
+                }
+                false
+            }
+            Msg::SelectPreset(name) => {
+                match self.presets.get(&name) {
+                    Some(preset) => {
+                        self.params = preset.params.clone();
+                        self.filters = preset.filters.clone();
+                        self.filters.store();
+                        self.preset_name = name.clone();
+                        self.selected_preset = name;
+                    }
+                    None => self.selected_preset.clear(),
+                }
+                true
+            }
+            Msg::UpdatePresetName(s) => {
+                self.preset_name = s;
+                false
+            }
+            Msg::SavePreset => {
+                let name = match self.preset_name.trim() {
+                    "" => self.params.summary(),
+                    name => name.to_owned(),
+                };
+                if Presets::is_reserved(&name) {
+                    self.presets_status = Some(format!("\"{}\" can't be used as a name", name));
+                    return true;
+                }
+                self.presets.save(Preset {
+                    name: name.clone(),
+                    params: self.params.clone(),
+                    filters: self.filters.clone(),
+                });
+                self.preset_name = name.clone();
+                self.selected_preset = name;
+                self.presets_status = None;
+                true
+            }
+            Msg::DeletePreset => {
+                self.presets.delete(&self.selected_preset);
+                self.selected_preset.clear();
+                self.presets_status = None;
+                true
+            }
+            Msg::ExportPresets => {
+                let json = self.presets.export(self.tz_offset);
+                if let Err(e) = download::download("saved-searches.json", "application/json", &json)
+                {
+                    self.presets_status = Some(format!("Export failed: {:?}", e));
+                }
+                true
+            }
+            Msg::ImportPresets(file) => {
+                ctx.link().send_future(async move {
+                    let text = gloo_file::futures::read_as_text(&file.into()).await;
+                    Msg::PresetsImported(text.map_err(|e| e.to_string()))
+                });
+                false
+            }
+            Msg::PresetsImported(text) => {
+                let imported = text.and_then(|t| {
+                    self.presets
+                        .import(&t, self.tz_offset)
+                        .map_err(|e| e.to_string())
+                });
+                self.presets_status = Some(match imported {
+                    Ok(n) => format!("Imported {} saved searches", n),
+                    Err(e) => format!("Import failed: {}", e),
+                });
+                true
+            }
+            Msg::Expand(x) => {
+                self.lightbox = Some(x);
+                true
+            }
+            Msg::CloseLightbox => {
+                self.lightbox = None;
+                true
+            }
+            Msg::SetLiveState(x) => {
+                match x {
+                    Ok(states) => {
+                        Rc::make_mut(&mut self.live)
+                            .extend(states.into_iter().map(|(k, v)| (k, Rc::new(v))));
+                        if let EnrichState::Working { done, total } = &mut self.enrich {
+                            *done += 1;
+                            if done == total {
+                                self.enrich = EnrichState::Idle;
+                            }
+                        }
+                        self.enrich_next(ctx);
+                    }
+                    Err(e) => {
+                        self.enrich_queue.clear();
+                        self.enrich = EnrichState::Failed(e);
+                    }
+                }
+                true
+            }
         }
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
         // Search box
-        let mut elems = vec![self.search_form(ctx)];
+        let mut elems = vec![
+            self.search_form(ctx),
+            self.restore_prompt(ctx),
+            self.history_panel(ctx),
+            self.index_search(ctx),
+        ];
 
         // Results
-        if !self.results.is_empty() {
+        if !self.results.is_empty() || self.imported.is_some() || self.index_hits.is_some() {
+            let items = self.filtered_results();
             elems.push(html! {
                 <div class="results">
-                    {for self.results.iter().map(|r| r.html()).chain(std::iter::once(self.more_button(ctx)))}
+                    {self.search_info()}
+                    {self.index_info()}
+                    {self.imported_info(ctx)}
+                    <ResultList items={Rc::new(items)}
+                        generation={self.results_generation}
+                        live={self.live.clone()}
+                        sensitive_media={self.settings.sensitive_media}
+                        on_expand={self.on_expand.clone()}
+                        new_items={self.new_items.clone()}
+                        virtualize={self.settings.virtualize} />
+                    {self.infinite_scroll(ctx)}
+                    {self.more_button(ctx)}
+                    {self.enrich_button(ctx)}
+                    {self.watch_button(ctx)}
+                    {self.export_panel(ctx)}
                 </div>
             });
+        } else if self.last_params.is_some() && matches!(self.state, FetchState::Done) {
+            // Nothing found yet, which is when watching is most useful
+            elems.push(self.watch_button(ctx));
         }
 
         // Error message
         if let FetchState::Failed(err) = &self.state {
+            // Not re-rendered while waiting, so no countdown
+            let retry = if self.settings.infinite_scroll && !self.results.is_empty() {
+                " (retrying shortly)"
+            } else {
+                ""
+            };
             elems.push(html! {
                 <div class="error">
-                    {err}
+                    {err}{retry}
                 </div>
             });
         }
+        if let EnrichState::Failed(err) = &self.enrich {
+            elems.push(html! {
+                <div class="error">
+                    {format!("Checking live state failed: {}", err)}
+                </div>
+            });
+        }
+
+        elems.push(html! {
+            <DiagnosticsPanel entries={self.diagnostics.entries()}
+                tz_offset={self.tz_offset}
+                on_copy={ctx.link().callback(Msg::CopyRequest)}
+                on_open={ctx.link().callback(Msg::OpenResponse)} />
+        });
+
+        // Expanded media
+        if let Some((media, index)) = &self.lightbox {
+            let on_close = ctx.link().callback(|_| Msg::CloseLightbox);
+            elems.push(html! {
+                <Lightbox media={media.clone()} index={*index} {on_close} />
+            });
+        }
 
         html! {
             <div>{ for elems.into_iter() }</div>
@@ -163,11 +1319,19 @@ impl Component for Model {
 impl Model {
     fn search_form(&self, ctx: &Context<Self>) -> Html {
         let on_endpoint_change = ctx.link().callback(Msg::UpdateEndpoint);
+        let on_preset_change = ctx.link().callback(Msg::SelectPreset);
         let on_subreddit_change = ctx.link().callback(Msg::UpdateSubreddit);
         let on_author_change = ctx.link().callback(Msg::UpdateAuthor);
         let on_query_change = ctx.link().callback(Msg::UpdateQuery);
         let on_time_start_change = ctx.link().callback(Msg::UpdateTimeStart);
         let on_time_end_change = ctx.link().callback(Msg::UpdateTimeEnd);
+        let on_removed_filter_change = ctx.link().callback(Msg::UpdateRemovedFilter);
+        let on_live_api_change = ctx.link().callback(Msg::UpdateLiveApi);
+        let on_sensitive_media_change = ctx.link().callback(Msg::UpdateSensitiveMedia);
+        let on_virtualize_change = ctx.link().callback(Msg::UpdateVirtualize);
+        let on_infinite_scroll_change = ctx.link().callback(Msg::UpdateInfiniteScroll);
+        let on_watch_interval_change = ctx.link().callback(Msg::UpdateWatchInterval);
+        let on_watch_notify_change = ctx.link().callback(Msg::UpdateWatchNotify);
         let on_submit = ctx.link().callback(|e: FocusEvent| {
             e.prevent_default();
             Msg::Search
@@ -191,6 +1355,14 @@ impl Model {
                         on_input={on_endpoint_change}
                         options={Endpoint::list()}
                         selected={self.params.endpoint.to_string()} />
+                    <div class="spacer" />
+                    <Select width={Width::Half}
+                        id={"preset"}
+                        class={"endpoint"}
+                        label={"Saved search:"}
+                        on_input={on_preset_change}
+                        options={std::iter::once(NO_PRESET.to_owned()).chain(self.presets.names()).collect::<Vec<_>>()}
+                        selected={self.selected_preset.clone()} />
                 </div>
 
                 <div>
@@ -208,15 +1380,19 @@ impl Model {
                 </div>
 
                 <div>
-                    <SearchBox width={Width::Half}
+                    <DateTimePicker width={Width::Half}
                         id={"time_start"}
                         label={"After:"}
+                        max={self.params.time_end.clone()}
+                        tz_offset={self.tz_offset}
                         on_change={on_time_start_change}
                         value={self.params.time_start.clone()} />
                     <div class="spacer" />
-                    <SearchBox width={Width::Half}
+                    <DateTimePicker width={Width::Half}
                         id={"time_end"}
                         label={"Before:"}
+                        min={self.params.time_start.clone()}
+                        tz_offset={self.tz_offset}
                         on_change={on_time_end_change}
                         value={self.params.time_end.clone()} />
                 </div>
@@ -229,14 +1405,418 @@ impl Model {
                         value={self.params.query.clone()} />
                 </div>
 
-                <SearchButton state={search_state} />
+                <div>
+                    <Select width={Width::Half}
+                        id={"removed_filter"}
+                        class={"filter"}
+                        label={"Removed content:"}
+                        on_input={on_removed_filter_change}
+                        options={RemovedFilter::list()}
+                        selected={self.filters.removed.to_string()} />
+                </div>
 
-                <script src={"bundle.js"}></script>
+                {self.presets_editor(ctx)}
+                {self.corpus_panel(ctx)}
+
+                <details class="settings">
+                    <summary>{"Settings"}</summary>
+                    <div class="search_full">
+                        <label for="live_api">{"Live state API:"}</label>
+                        <TextInput id={"live_api"}
+                            on_change={on_live_api_change}
+                            value={self.settings.live_api.clone()} />
+                    </div>
+                    <Select width={Width::Full}
+                        id={"sensitive_media"}
+                        class={""}
+                        label={"NSFW and spoiler media:"}
+                        on_input={on_sensitive_media_change}
+                        options={SensitiveMedia::list()}
+                        selected={self.settings.sensitive_media.to_string()} />
+                    <Checkbox id={"virtualize"}
+                        label={"Only render results near the screen (disable to use find in page)"}
+                        checked={self.settings.virtualize}
+                        on_change={on_virtualize_change} />
+                    <Checkbox id={"infinite_scroll"}
+                        label={"Load more results when scrolling to the bottom"}
+                        checked={self.settings.infinite_scroll}
+                        on_change={on_infinite_scroll_change} />
+                    <div class="search_full">
+                        <label for="watch_interval">{"Seconds between checks when watching:"}</label>
+                        <TextInput id={"watch_interval"}
+                            on_change={on_watch_interval_change}
+                            value={self.settings.watch_interval.to_string()} />
+                    </div>
+                    <Checkbox id={"watch_notify"}
+                        label={"Show a notification when a watched search has new results"}
+                        checked={self.settings.watch_notify}
+                        on_change={on_watch_notify_change} />
+                </details>
+
+                <SearchButton state={search_state} />
+                {self.import_input(ctx)}
             </form>
         }
     }
 
+    /// Link that runs a search with the current filters when opened
+    fn deep_link(&self, params: &SearchParams) -> DeepLink {
+        DeepLink {
+            params: params.clone(),
+            filters: self.filters.clone(),
+            run: true,
+        }
+    }
+
+    /// Start a search with the form's parameters, discarding previous results
+    fn new_search(&mut self, ctx: &Context<Self>) {
+        self.restorable = None;
+        self.results.clear();
+        self.results_generation += 1;
+        self.exhausted = false;
+        self.failures = 0;
+        self.backoff_until = 0.0;
+        self.stop_watch();
+        self.stop_dump();
+        self.cancel_searches();
+        self.imported = None;
+        self.search(ctx, SearchType::Initial);
+        self.record_history();
+    }
+
+    fn history_panel(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <HistoryPanel entries={self.history.entries()}
+                tz_offset={self.tz_offset}
+                on_run={ctx.link().callback(Msg::RunHistory)}
+                on_toggle_pinned={ctx.link().callback(Msg::TogglePinned)}
+                on_rename={ctx.link().callback(Msg::RenameHistory)}
+                on_delete={ctx.link().callback(Msg::DeleteHistory)} />
+        }
+    }
+
+    /// Add the search that was just started to the search history
+    fn record_history(&mut self) {
+        let backend = if self.local {
+            corpus::BACKEND.to_owned()
+        } else {
+            self.params.endpoint.backend()
+        };
+        let id = self.history.record(self.params.clone(), backend);
+        self.history_entry = Some(id);
+    }
+
+    /// Remember the results of the current history entry
+    fn cache_results(&mut self) {
+        let params = match &self.last_params {
+            Some(p) => p.clone(),
+            None => return,
+        };
+
+        self.history_cache.retain(|c| c.id != self.history_id);
+        if self.history_cache.len() >= HISTORY_CACHE_SIZE {
+            self.history_cache.remove(0);
+        }
+        self.history_cache.push(CachedSearch {
+            id: self.history_id,
+            params,
+            results: self.results.clone(),
+            exhausted: self.exhausted,
+            queries: self.queries.clone(),
+            local: self.local,
+            metadata: self.metadata.clone(),
+        });
+    }
+
+    fn presets_editor(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <PresetsEditor name={self.preset_name.clone()}
+                can_delete={self.presets.get(&self.selected_preset).is_some()}
+                status={self.presets_status.clone()}
+                on_name_change={ctx.link().callback(Msg::UpdatePresetName)}
+                on_save={ctx.link().callback(|_| Msg::SavePreset)}
+                on_delete={ctx.link().callback(|_| Msg::DeletePreset)}
+                on_export={ctx.link().callback(|_| Msg::ExportPresets)}
+                on_import={ctx.link().callback(Msg::ImportPresets)} />
+        }
+    }
+
+    fn corpus_panel(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <CorpusPanel enabled={self.settings.corpus}
+                only={self.settings.corpus_only}
+                stats={self.corpus_stats}
+                status={self.corpus_status.clone()}
+                on_enabled_change={ctx.link().callback(Msg::UpdateCorpus)}
+                on_only_change={ctx.link().callback(Msg::UpdateCorpusOnly)}
+                on_refresh={ctx.link().callback(|_| Msg::RefreshCorpusStats)}
+                on_purge={ctx.link().callback(Msg::PurgeCorpus)} />
+        }
+    }
+
+    /// Keep fetched results in the local corpus, if enabled
+    fn add_to_corpus(&self, ctx: &Context<Self>, items: &[RedditType]) {
+        if !self.settings.corpus || items.is_empty() {
+            return;
+        }
+        let records: Vec<_> = items.iter().map(Record::new).collect();
+        ctx.link()
+            .send_future(async { Msg::CorpusStored(corpus::store(records).await) });
+    }
+
+    /// Run the search form against the local corpus
+    fn search_corpus(&mut self, ctx: &Context<Self>) {
+        let params = self.params.clone();
+        self.local = true;
+        self.queries.clear();
+        let id = self.add_job(Job::CorpusSearch(params.clone()));
+        ctx.link()
+            .send_future(async move { Msg::CorpusRead((id, corpus::search(&params).await)) });
+        ctx.link()
+            .send_message(Msg::SetPsFetchState(FetchState::Fetching));
+    }
+
+    fn restore_prompt(&self, ctx: &Context<Self>) -> Html {
+        let session = match &self.restorable {
+            Some(s) => s,
+            None => return html! {},
+        };
+        let text = format!(
+            "Restore {} results of \"{}\" from {}?",
+            session.len(),
+            session.params.summary(),
+            format_timestamp(session.saved, self.tz_offset)
+        );
+        html! {
+            <div class="info">
+                {text}{" "}
+                <button type="button" class="history_button" onclick={ctx.link().callback(|_| Msg::RestoreSession)}>
+                    {"Restore"}
+                </button>
+                <button type="button" class="history_button" onclick={ctx.link().callback(|_| Msg::DismissSession)}>
+                    {"Dismiss"}
+                </button>
+            </div>
+        }
+    }
+
+    /// Snapshot the search's results, so they survive the tab being closed
+    fn save_session(&self, ctx: &Context<Self>) {
+        // Files can be opened again, and an empty snapshot would replace one worth restoring
+        let params = match &self.last_params {
+            Some(p) if self.imported.is_none() && !self.results.is_empty() => p.clone(),
+            _ => return,
+        };
+        let session = Session::new(
+            params,
+            &self.results,
+            self.exhausted,
+            self.queries.clone(),
+            gloo_utils::window().scroll_y().unwrap_or_default(),
+        );
+        ctx.link()
+            .send_future(async move { Msg::SessionSaved(session::save(&session).await) });
+    }
+
+    fn schedule_save(&mut self, ctx: &Context<Self>) {
+        self.session_generation += 1;
+        schedule(
+            ctx,
+            SESSION_SAVE_DELAY_MS,
+            Msg::SaveSession(self.session_generation),
+        );
+    }
+
+    /// Imported and local results have no search to continue, and index
+    /// results are not the search's results
+    fn can_continue(&self) -> bool {
+        self.imported.is_none() && !self.local && self.index_hits.is_none()
+    }
+
+    fn index_search(&self, ctx: &Context<Self>) -> Html {
+        if self.index.is_empty() && !self.settings.corpus {
+            return html! {};
+        }
+        html! {
+            <div class="search index_search">
+                <SearchBox width={Width::Full}
+                    id={"index_query"}
+                    label={"Search collected results (\"phrase\", OR, -word, parentheses):"}
+                    on_change={ctx.link().callback(Msg::UpdateIndexQuery)}
+                    value={self.index_query.clone()} />
+            </div>
+        }
+    }
+
+    /// Keep the metadata of a page, the total of later pages only counts what is left
+    fn update_metadata(&mut self, search_type: &SearchType, metadata: Option<Metadata>) {
+        let offset = match search_type {
+            SearchType::Initial => 0,
+            SearchType::More => self.results.len() as u64,
+            // Polls only ask for what is new
+            SearchType::Watch(_) => return,
+        };
+        self.metadata = metadata.map(|mut m| {
+            m.total_results = m.total_results.map(|t| t + offset);
+            m
+        });
+    }
+
+    fn search_info(&self) -> Html {
+        match &self.metadata {
+            Some(m) if self.index_hits.is_none() => html! {
+                <SearchInfo metadata={m.clone()} results={self.results.len()} />
+            },
+            _ => html! {},
+        }
+    }
+
+    fn index_info(&self) -> Html {
+        match &self.index_hits {
+            Some(hits) => html! {
+                <div class="info">
+                    {format!("{} of {} collected results match, most relevant first", hits.len(), self.index.len())}
+                </div>
+            },
+            None => html! {},
+        }
+    }
+
+    /// Index results added from `start` on
+    fn index_results(&mut self, start: usize) {
+        self.index.extend(&self.results[start..]);
+        self.refresh_hits();
+    }
+
+    fn refresh_hits(&mut self) {
+        self.index_hits = if self.index_query.trim().is_empty() {
+            None
+        } else {
+            Some(Rc::new(self.index.search(&self.index_query)))
+        };
+    }
+
+    fn import_input(&self, ctx: &Context<Self>) -> Html {
+        let error = match &self.import_error {
+            Some(e) => html! { <div class="error">{e}</div> },
+            None => html! {},
+        };
+        html! {
+            <div class="import">
+                <FileInput id={"import_results"}
+                    label={"Open exported results"}
+                    accept={".json,.ndjson,application/json,application/x-ndjson"}
+                    on_change={ctx.link().callback(Msg::ImportResults)} />
+                <FileInput id={"search_dump"}
+                    label={"Search a dump file"}
+                    accept={".zst,.ndjson,.jsonl,.json"}
+                    on_change={ctx.link().callback(Msg::SearchDump)} />
+                {error}
+            </div>
+        }
+    }
+
+    fn imported_info(&self, ctx: &Context<Self>) -> Html {
+        match &self.imported {
+            Some(name) => html! {
+                <ImportedInfo name={name.clone()}
+                    results={self.results.len()}
+                    scan={self.dump.as_ref().map(|d| d.status.clone())}
+                    on_cancel={ctx.link().callback(|_| Msg::CancelDump)} />
+            },
+            None => html! {},
+        }
+    }
+
+    /// Clear the results to show ones read from a file
+    fn show_file(&mut self, name: String) {
+        self.stop_watch();
+        self.stop_dump();
+        self.cancel_searches();
+        self.results.clear();
+        self.results_generation += 1;
+        self.state = FetchState::Done;
+        self.exhausted = true;
+        self.last_params = None;
+        self.queries.clear();
+        self.history_entry = None;
+        self.imported = Some(name);
+        self.import_error = None;
+        self.metadata = None;
+    }
+
+    fn stop_dump(&mut self) {
+        if let Some(scan) = self.dump.take() {
+            if scan.status.running {
+                self.worker.send(ParseRequest::CancelScan { id: scan.id });
+                revoke_object_url(&scan.url);
+            }
+        }
+    }
+
+    /// Forget requests of the searches shown before, their responses are ignored
+    fn cancel_searches(&mut self) {
+        self.jobs.retain(|_, job| {
+            !matches!(
+                job,
+                Job::Search(..) | Job::CorpusSearch(_) | Job::Restore(_)
+            )
+        });
+    }
+
+    /// Show the results of the last session as they were left
+    fn restore_session(&mut self, ctx: &Context<Self>, session: Session, results: Vec<RedditType>) {
+        self.results = results.into_iter().map(Rc::new).collect();
+        self.results_generation += 1;
+        self.index_results(0);
+        self.params = session.params.clone();
+        self.last_params = Some(session.params);
+        self.exhausted = session.exhausted;
+        self.queries = session.queries;
+        self.imported = None;
+        self.local = false;
+        self.metadata = None;
+        self.state = FetchState::Done;
+
+        self.history_id = self.next_history_id;
+        self.next_history_id += 1;
+        if let Some(params) = &self.last_params {
+            self.deep_link(params)
+                .replace_location(self.tz_offset, self.history_id);
+        }
+        self.cache_results();
+        // Scroll once the results are rendered
+        schedule(ctx, 0, Msg::RestoreScroll(session.scroll_y));
+    }
+
+    /// Remember what to do with the results of a new worker job
+    fn add_job(&mut self, job: Job) -> u32 {
+        let id = self.next_job;
+        self.next_job += 1;
+        self.jobs.insert(id, job);
+        id
+    }
+
+    fn infinite_scroll(&self, ctx: &Context<Self>) -> Html {
+        if !self.settings.infinite_scroll || !self.can_continue() {
+            return html! {};
+        }
+        if self.exhausted {
+            return html! {
+                <div class="info">{"No more results"}</div>
+            };
+        }
+
+        let on_change = ctx.link().callback(Msg::SentinelVisible);
+        html! {
+            <Sentinel {on_change} />
+        }
+    }
+
     fn more_button(&self, ctx: &Context<Self>) -> Html {
+        if !self.can_continue() {
+            return html! {};
+        }
         let on_click = ctx.link().callback(|_| Msg::More);
         let state = if matches!(self.state, FetchState::Fetching) {
             SearchState::Working("Fetching...".to_string())
@@ -249,33 +1829,214 @@ impl Model {
         }
     }
 
+    fn enrich_button(&self, ctx: &Context<Self>) -> Html {
+        let on_click = ctx.link().callback(|_| Msg::Enrich);
+        let state = if let EnrichState::Working { done, total } = self.enrich {
+            SearchState::Working(format!("Checking live state {}/{}...", done, total))
+        } else {
+            SearchState::Idle("Check live state".to_string())
+        };
+
+        html! {
+            <SearchButton {state} {on_click} />
+        }
+    }
+
+    /// Results that pass the client-side filters
+    fn filtered_results(&self) -> Vec<Rc<RedditType>> {
+        let results = match &self.index_hits {
+            Some(hits) => hits.as_slice(),
+            None => &self.results,
+        };
+        results
+            .iter()
+            .filter(|r| self.filters.matches(r))
+            .cloned()
+            .collect()
+    }
+
+    fn export_panel(&self, ctx: &Context<Self>) -> Html {
+        html! {
+            <ExportPanel options={self.export.clone()}
+                error={self.export_error.clone()}
+                on_format_change={ctx.link().callback(Msg::UpdateExportFormat)}
+                on_column_change={ctx.link().callback(Msg::UpdateExportColumn)}
+                on_download={ctx.link().callback(|_| Msg::Export)}
+                on_download_archive={ctx.link().callback(|_| Msg::ExportArchive)} />
+        }
+    }
+
+    fn watch_button(&self, ctx: &Context<Self>) -> Html {
+        if !self.can_continue() {
+            return html! {};
+        }
+        let on_click = ctx.link().callback(|_| Msg::ToggleWatch);
+        let (text, error) = match &self.watch {
+            Some(w) => (
+                format!(
+                    "Stop watching (checking every {} s)",
+                    self.settings.watch_interval_ms() / 1000
+                ),
+                w.error.clone(),
+            ),
+            None => ("Watch for new results".to_string(), None),
+        };
+        let error = match error {
+            Some(e) => html! { <div class="error">{format!("Watching failed: {}", e)}</div> },
+            None => html! {},
+        };
+
+        html! {
+            <>
+                <SearchButton state={SearchState::Idle(text)} {on_click} />
+                {error}
+            </>
+        }
+    }
+
+    /// Stop watching and forget which results were new
+    fn stop_watch(&mut self) {
+        self.watch = None;
+        self.new_items = Rc::new(HashSet::new());
+        self.unread = 0;
+        self.update_title();
+    }
+
+    /// Prepend results found by watching that aren't shown yet
+    fn add_new_items(&mut self, ctx: &Context<Self>, items: Vec<RedditType>) {
+        let seen: HashSet<_> = self.results.iter().map(|r| r.fullname()).collect();
+        let new: Vec<_> = items
+            .into_iter()
+            .filter(|r| !seen.contains(&r.fullname()))
+            .map(Rc::new)
+            .collect();
+        if new.is_empty() {
+            return;
+        }
+
+        let mut new_items = (*self.new_items).clone();
+        new_items.extend(new.iter().map(|r| r.fullname()));
+        self.new_items = Rc::new(new_items);
+        if gloo_utils::document().hidden() {
+            self.unread += new.len();
+            self.update_title();
+        }
+        if self.settings.watch_notify {
+            let search = self.last_params.as_ref().map(|p| p.summary());
+            notify(new.len(), &search.unwrap_or_default());
+        }
+
+        self.index.extend(&new);
+        self.refresh_hits();
+        self.results.splice(0..0, new);
+        self.schedule_save(ctx);
+        if let Some(id) = self.history_entry {
+            self.history.set_results(id, self.results.len());
+        }
+        self.cache_results();
+    }
+
+    /// Show the number of unseen results in the tab title
+    fn update_title(&self) {
+        let title = if self.unread > 0 {
+            format!("({}) {}", self.unread, self.title)
+        } else {
+            self.title.clone()
+        };
+        gloo_utils::document().set_title(&title);
+    }
+
+    fn enrich(&mut self, ctx: &Context<Self>) {
+        // The archived texts are kept to diff the live ones against
+        let items: Vec<_> = self
+            .results
+            .iter()
+            .map(|r| (r.fullname(), r.text().to_owned()))
+            .collect();
+        // Reversed to pop them in order
+        let batches: Vec<_> = items
+            .chunks(live::BATCH_SIZE)
+            .rev()
+            .map(|c| c.to_vec())
+            .collect();
+        if batches.is_empty() {
+            return;
+        }
+        self.enrich = EnrichState::Working {
+            done: 0,
+            total: batches.len(),
+        };
+        self.enrich_queue = batches;
+        self.enrich_next(ctx);
+    }
+
+    /// Query the next batch, batches go one at a time and stop at the first failure
+    fn enrich_next(&mut self, ctx: &Context<Self>) {
+        let batch = match self.enrich_queue.pop() {
+            Some(b) => b,
+            None => return,
+        };
+        let api = self.settings.live_api().to_owned();
+        let fullnames: Vec<_> = batch.iter().map(|(f, _)| f.clone()).collect();
+        let url = match live::info_url(&api, &fullnames) {
+            Ok(url) => url,
+            Err(e) => {
+                ctx.link()
+                    .send_message(Msg::SetLiveState(Err(e.to_string())));
+                return;
+            }
+        };
+        let backend = Url::parse(&url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_owned))
+            .unwrap_or_default();
+        let id = self.add_job(Job::Enrich(batch));
+        self.diagnostics.start(id, url.clone(), backend, 0);
+        fetch_job(ctx, id, url);
+    }
+
     fn search(&mut self, ctx: &Context<Self>, search_type: SearchType) {
+        if matches!(search_type, SearchType::Initial) {
+            if self.settings.search_corpus() {
+                return self.search_corpus(ctx);
+            }
+            self.local = false;
+            self.metadata = None;
+        }
+
         let params = match search_type {
             SearchType::Initial => self.params.clone(),
-            SearchType::More => self.last_params.clone().unwrap(),
+            SearchType::More | SearchType::Watch(_) => self.last_params.clone().unwrap(),
         };
 
         let url = {
-            let mut url = Url::parse(self.params.endpoint.url()).unwrap();
+            let mut url = Url::parse(params.endpoint.url()).unwrap();
 
             // Add GET query parameters
             url.query_pairs_mut()
                 .append_pair("limit", "1000");
+            // Backends that don't know this ignore it
+            url.query_pairs_mut().append_pair("metadata", "true");
 
-            if !self.params.subreddit.is_empty() {
+            if !params.subreddit.is_empty() {
                 url.query_pairs_mut()
                     .append_pair("subreddit", &params.subreddit);
             }
 
-            if !self.params.author.is_empty() {
+            if !params.author.is_empty() {
                 url.query_pairs_mut().append_pair("author", &params.author);
             }
 
-            if !self.params.query.is_empty() {
+            if !params.query.is_empty() {
                 url.query_pairs_mut().append_pair("q", &params.query);
             }
 
-            if let Some(ts) = parse_time(&params.time_start, self.tz_offset) {
+            // When watching, only ask for results newer than the ones shown
+            let after = match search_type {
+                SearchType::Watch(_) => self.results.first().map(|r| r.time()),
+                _ => None,
+            };
+            if let Some(ts) = after.or_else(|| parse_time(&params.time_start, self.tz_offset)) {
                 url.query_pairs_mut().append_pair("after", &ts.to_string());
             }
 
@@ -292,33 +2053,65 @@ impl Model {
             url.to_string()
         };
 
-        // Message to send when search finishes
-        {
-            let tz_offset = self.tz_offset;
-            let endpoint = self.params.endpoint.clone();
-            ctx.link().send_future(async move {
-                match fetch(url).await {
-                    Ok(x) => match endpoint.parse(x, tz_offset) {
-                        Ok(p) => Msg::SetPsFetchState(FetchState::Success(p, search_type, params)),
-                        Err(e) => Msg::SetPsFetchState(FetchState::Failed(e.to_string())),
-                    },
-                    Err(e) => Msg::SetPsFetchState(FetchState::Failed(e.to_string())),
-                }
-            });
+        if matches!(search_type, SearchType::Initial) {
+            self.queries.clear();
         }
+        self.queries
+            .push((url.clone(), (js_sys::Date::now() / 1000.0) as i64));
 
-        ctx.link()
-            .send_message(Msg::SetPsFetchState(FetchState::Fetching));
+        // The response is parsed by the worker
+        let watching = matches!(search_type, SearchType::Watch(_));
+        let attempt = match (&self.watch, watching) {
+            (Some(w), true) => w.failures,
+            _ => self.failures,
+        };
+        let endpoint = params.endpoint.clone();
+        let backend = endpoint.backend();
+        let id = self.add_job(Job::Search(search_type, params, endpoint));
+        self.diagnostics.start(id, url.clone(), backend, attempt);
+        fetch_job(ctx, id, url);
+
+        if !watching {
+            ctx.link()
+                .send_message(Msg::SetPsFetchState(FetchState::Fetching));
+        }
+    }
+}
+
+/// Send a message to the model after a delay
+fn schedule(ctx: &Context<Model>, ms: u32, msg: Msg) {
+    ctx.link().send_future(async move {
+        TimeoutFuture::new(ms).await;
+        msg
+    });
+}
+
+fn revoke_object_url(url: &str) {
+    if let Err(e) = web_sys::Url::revoke_object_url(url) {
+        log::warn!("Unable to release file: {:?}", e);
+    }
+}
+
+/// Show a browser notification about new results, if the user allowed them
+fn notify(count: usize, search: &str) {
+    if Notification::permission() != NotificationPermission::Granted {
+        return;
+    }
+    let mut options = NotificationOptions::new();
+    options.body(search);
+    let title = format!("{} new results", count);
+    if let Err(e) = Notification::new_with_options(&title, &options) {
+        log::warn!("Unable to show notification: {:?}", e);
     }
 }
 
-fn parse_time(s: impl AsRef<str>, offset: i64) -> Option<i64> {
-    let format = format_description::parse("[year]-[month]-[day] [hour]:[minute]").unwrap();
-    let ts = PrimitiveDateTime::parse(s.as_ref(), &format)
-        .ok()?
-        .assume_offset(UtcOffset::from_whole_seconds(60 * offset as i32).ok()?)
-        .unix_timestamp();
-    Some(ts)
+/// Fetch the response of a job, it comes back as `Msg::Fetched`
+fn fetch_job(ctx: &Context<Model>, id: u32, url: String) {
+    ctx.link().send_future(async move {
+        let start = js_sys::Date::now();
+        let result = fetch_with_status(url).await;
+        Msg::Fetched((id, js_sys::Date::now() - start, result))
+    });
 }
 
 fn main() {
@@ -0,0 +1,446 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{json, Value};
+use web_sys::{HtmlImageElement, MouseEvent};
+use yew::prelude::*;
+
+use crate::pushshift::Leniency;
+use crate::settings::SensitiveMedia;
+
+/// Preferred width of inline previews
+const PREVIEW_WIDTH: u64 = 640;
+
+static IMAGE_EXTENSIONS: &[&str] = &[".jpg", ".jpeg", ".png", ".gif", ".webp"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MediaKind {
+    Image,
+    Video,
+}
+
+/// A single image or video attached to a submission
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Media {
+    pub kind: MediaKind,
+    /// Full size image, or video source
+    pub url: String,
+    /// Downscaled image for inline display, also used as video poster
+    pub preview: String,
+    pub caption: Option<String>,
+}
+
+/// An entry of `gallery_data.items`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GalleryItem {
+    pub media_id: String,
+    pub caption: Option<String>,
+}
+
+fn url_at(v: &Value, pointer: &str) -> Option<String> {
+    v.pointer(pointer)
+        .and_then(Value::as_str)
+        .filter(|s| !s.is_empty())
+        .map(|s| html_escape::decode_html_entities(s).into_owned())
+}
+
+fn width_of(v: &Value) -> u64 {
+    v.get("x")
+        .or_else(|| v.get("width"))
+        .and_then(Value::as_u64)
+        .unwrap_or(0)
+}
+
+/// Pick the smallest rendition that is at least `PREVIEW_WIDTH` wide, or the largest one
+fn pick_preview<'a>(renditions: impl Iterator<Item = &'a Value>) -> Option<&'a Value> {
+    let mut renditions: Vec<_> = renditions.collect();
+    renditions.sort_by_key(|v| width_of(v));
+    renditions
+        .iter()
+        .find(|v| width_of(v) >= PREVIEW_WIDTH)
+        .or_else(|| renditions.last())
+        .copied()
+}
+
+pub fn deserialize_media_metadata<'de, D>(deserializer: D) -> Result<Vec<(String, Media)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Vec::<(String, Media)>::deserialize(deserializer);
+    }
+    let map = match Value::deserialize(deserializer)? {
+        Value::Object(map) => map,
+        Value::Null => return Ok(Vec::new()),
+        _ => {
+            Leniency::skipped();
+            return Ok(Vec::new());
+        }
+    };
+
+    let media = map
+        .into_iter()
+        .filter_map(|(id, item)| {
+            if item.get("status").and_then(Value::as_str) != Some("valid") {
+                return None;
+            }
+            let source = item.get("s")?;
+            let preview = item
+                .get("p")
+                .and_then(Value::as_array)
+                .and_then(|p| pick_preview(p.iter()))
+                .and_then(|p| url_at(p, "/u"));
+
+            let media = match item.get("e").and_then(Value::as_str)? {
+                "Image" => {
+                    let url = url_at(source, "/u")?;
+                    Media {
+                        kind: MediaKind::Image,
+                        preview: preview.unwrap_or_else(|| url.clone()),
+                        url,
+                        caption: None,
+                    }
+                }
+                "AnimatedImage" => match url_at(source, "/mp4") {
+                    Some(url) => Media {
+                        kind: MediaKind::Video,
+                        preview: preview.or_else(|| url_at(source, "/gif"))?,
+                        url,
+                        caption: None,
+                    },
+                    None => {
+                        let url = url_at(source, "/gif")?;
+                        Media {
+                            kind: MediaKind::Image,
+                            preview: url.clone(),
+                            url,
+                            caption: None,
+                        }
+                    }
+                },
+                _ => return None,
+            };
+            Some((id, media))
+        })
+        .collect();
+    Ok(media)
+}
+
+pub fn deserialize_gallery_data<'de, D>(deserializer: D) -> Result<Vec<GalleryItem>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Vec::<GalleryItem>::deserialize(deserializer);
+    }
+    let value = Value::deserialize(deserializer)?;
+    let items = match value.get("items").and_then(Value::as_array) {
+        Some(items) => items,
+        None => return Ok(Vec::new()),
+    };
+
+    let items = items
+        .iter()
+        .filter_map(|item| {
+            Some(GalleryItem {
+                media_id: item.get("media_id")?.as_str()?.to_owned(),
+                caption: item
+                    .get("caption")
+                    .and_then(Value::as_str)
+                    .filter(|s| !s.is_empty())
+                    .map(str::to_owned),
+            })
+        })
+        .collect();
+    Ok(items)
+}
+
+pub fn deserialize_preview<'de, D>(deserializer: D) -> Result<Option<Box<Media>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Option::<Box<Media>>::deserialize(deserializer);
+    }
+    let value = Value::deserialize(deserializer)?;
+    let image = match value.pointer("/images/0") {
+        Some(image) => image,
+        None => return Ok(None),
+    };
+
+    let url = match url_at(image, "/source/url") {
+        Some(url) => url,
+        None => return Ok(None),
+    };
+    let preview = image
+        .get("resolutions")
+        .and_then(Value::as_array)
+        .and_then(|r| pick_preview(r.iter()))
+        .and_then(|r| url_at(r, "/url"))
+        .unwrap_or_else(|| url.clone());
+
+    // Animated previews come with an mp4 variant
+    let media = match url_at(image, "/variants/mp4/source/url") {
+        Some(mp4) => Media {
+            kind: MediaKind::Video,
+            url: mp4,
+            preview,
+            caption: None,
+        },
+        None => Media {
+            kind: MediaKind::Image,
+            url,
+            preview,
+            caption: None,
+        },
+    };
+    Ok(Some(Box::new(media)))
+}
+
+pub fn deserialize_secure_media<'de, D>(deserializer: D) -> Result<Option<Box<Media>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Option::<Box<Media>>::deserialize(deserializer);
+    }
+    let value = Value::deserialize(deserializer)?;
+
+    // Poster is filled in from the submission's preview, if any
+    if let Some(url) = url_at(&value, "/reddit_video/fallback_url") {
+        return Ok(Some(Box::new(Media {
+            kind: MediaKind::Video,
+            url,
+            preview: String::new(),
+            caption: None,
+        })));
+    }
+    if let Some(thumb) = url_at(&value, "/oembed/thumbnail_url") {
+        return Ok(Some(Box::new(Media {
+            kind: MediaKind::Image,
+            url: thumb.clone(),
+            preview: thumb,
+            caption: None,
+        })));
+    }
+    Ok(None)
+}
+
+/// URLs are stored entity encoded like in the archives
+fn encode_url(url: &str) -> String {
+    html_escape::encode_text(url).into_owned()
+}
+
+// The serializers write the subset of Reddit's format read by the deserializers,
+// binary formats get the parsed values
+
+pub fn serialize_media_metadata<S>(
+    media: &[(String, Media)],
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return media.serialize(serializer);
+    }
+    if media.is_empty() {
+        return serializer.serialize_none();
+    }
+    let map: serde_json::Map<_, _> = media
+        .iter()
+        .map(|(id, m)| {
+            let preview = json!([{ "u": encode_url(&m.preview), "x": PREVIEW_WIDTH }]);
+            let item = match m.kind {
+                MediaKind::Image => json!({
+                    "status": "valid",
+                    "e": "Image",
+                    "s": { "u": encode_url(&m.url) },
+                    "p": preview,
+                }),
+                MediaKind::Video => json!({
+                    "status": "valid",
+                    "e": "AnimatedImage",
+                    "s": { "mp4": encode_url(&m.url) },
+                    "p": preview,
+                }),
+            };
+            (id.clone(), item)
+        })
+        .collect();
+    map.serialize(serializer)
+}
+
+pub fn serialize_gallery_data<S>(items: &[GalleryItem], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return items.serialize(serializer);
+    }
+    if items.is_empty() {
+        return serializer.serialize_none();
+    }
+    let items: Vec<_> = items
+        .iter()
+        .map(|item| json!({ "media_id": item.media_id, "caption": item.caption }))
+        .collect();
+    json!({ "items": items }).serialize(serializer)
+}
+
+pub fn serialize_preview<S>(media: &Option<Box<Media>>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return media.serialize(serializer);
+    }
+    let m = match media {
+        Some(m) => m,
+        None => return serializer.serialize_none(),
+    };
+    let mut image = json!({
+        "source": { "url": encode_url(&m.url) },
+        "resolutions": [{ "url": encode_url(&m.preview), "width": PREVIEW_WIDTH }],
+    });
+    if m.kind == MediaKind::Video {
+        image["source"]["url"] = encode_url(&m.preview).into();
+        image["variants"] = json!({ "mp4": { "source": { "url": encode_url(&m.url) } } });
+    }
+    json!({ "images": [image] }).serialize(serializer)
+}
+
+pub fn serialize_secure_media<S>(
+    media: &Option<Box<Media>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if !serializer.is_human_readable() {
+        return media.serialize(serializer);
+    }
+    match media.as_deref() {
+        Some(m) if m.kind == MediaKind::Video => {
+            json!({ "reddit_video": { "fallback_url": encode_url(&m.url) } }).serialize(serializer)
+        }
+        Some(m) => {
+            json!({ "oembed": { "thumbnail_url": encode_url(&m.url) } }).serialize(serializer)
+        }
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Whether a link points directly to an image file
+pub fn is_image_url(url: &str) -> bool {
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase();
+    IMAGE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
+}
+
+/// Callback to open media in the lightbox, with the index to show first
+pub type OnExpand = Callback<(Vec<Media>, usize)>;
+
+fn media_onclick(media: &[Media], index: usize, on_expand: &OnExpand) -> Callback<MouseEvent> {
+    let media = media.to_vec();
+    let on_expand = on_expand.clone();
+    Callback::from(move |e: MouseEvent| {
+        // Cards are links to Reddit
+        e.prevent_default();
+        e.stop_propagation();
+        on_expand.emit((media.clone(), index));
+    })
+}
+
+pub fn bad_image_onerror() -> Callback<Event> {
+    Callback::from(|e: Event| {
+        if let Some(target) = e.target_dyn_into::<HtmlImageElement>() {
+            static BAD_IMAGE: &str = "bad-image.svg";
+            if !target.src().ends_with(BAD_IMAGE) {
+                target.set_src(BAD_IMAGE);
+            }
+        }
+    })
+}
+
+/// Inline preview of a submission's media, an image strip for galleries
+pub fn media_html(media: &[Media], on_expand: &OnExpand) -> Html {
+    let items = media.iter().enumerate().map(|(i, m)| {
+        let onclick = media_onclick(media, i, on_expand);
+        let video = if m.kind == MediaKind::Video {
+            html! { <span class="media_play">{"▶"}</span> }
+        } else {
+            html! {}
+        };
+
+        let image = if m.preview.is_empty() {
+            html! { <div class="media_image media_placeholder" /> }
+        } else {
+            html! {
+                <img class="media_image"
+                    loading="lazy"
+                    alt={m.caption.clone().unwrap_or_else(|| "Reddit media".to_owned())}
+                    src={m.preview.clone()}
+                    onerror={bad_image_onerror()} />
+            }
+        };
+
+        html! {
+            <div class="media_item" {onclick} title="Expand">
+                {image}
+                {video}
+            </div>
+        }
+    });
+
+    let count = if media.len() > 1 {
+        html! { <div class="media_count">{format!("{} items", media.len())}</div> }
+    } else {
+        html! {}
+    };
+
+    html! {
+        <div class="media">
+            <div class={classes!("media_strip", (media.len() > 1).then_some("gallery"))}>
+                {for items}
+            </div>
+            {count}
+        </div>
+    }
+}
+
+/// Wrap media of NSFW or spoiler submissions according to the user's setting
+///
+/// `label` names why the media is sensitive, `None` means it is shown as is.
+pub fn sensitive_html(
+    content: Html,
+    label: Option<&str>,
+    mode: SensitiveMedia,
+    on_reveal: Callback<()>,
+) -> Html {
+    let label = match (label, mode) {
+        (Some(label), SensitiveMedia::Blur | SensitiveMedia::Hide) => label,
+        _ => return content,
+    };
+
+    let onclick = Callback::from(move |e: MouseEvent| {
+        e.prevent_default();
+        e.stop_propagation();
+        on_reveal.emit(());
+    });
+
+    match mode {
+        SensitiveMedia::Blur => html! {
+            <div class="sensitive" {onclick} title="Click to reveal">
+                <div class="blurred">{content}</div>
+                <div class="sensitive_label">{label}</div>
+            </div>
+        },
+        _ => html! {
+            <div class="sensitive sensitive_hidden" {onclick} title="Click to reveal">
+                <div class="sensitive_label">{format!("{} media hidden, click to show", label)}</div>
+            </div>
+        },
+    }
+}
@@ -2,10 +2,13 @@ use std::fmt::Display;
 use std::str::FromStr;
 
 use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+use time::{format_description, OffsetDateTime, PrimitiveDateTime, UtcOffset};
 
-use crate::pushshift::{Reddit, RedditComment, RedditSubmission, RedditType};
+use crate::listing;
+use crate::pushshift::{Leniency, Page, Reddit, RedditComment, RedditSubmission, RedditType};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct SearchParams {
     pub endpoint: Endpoint,
     pub subreddit: String,
@@ -53,6 +56,27 @@ impl SearchParams {
         }
     }
 
+    /// Short human readable description of the search
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.endpoint.to_string()];
+        if !self.subreddit.is_empty() {
+            parts.push(format!("r/{}", self.subreddit));
+        }
+        if !self.author.is_empty() {
+            parts.push(format!("u/{}", self.author));
+        }
+        if !self.query.is_empty() {
+            parts.push(format!("\"{}\"", self.query));
+        }
+        if !self.time_start.is_empty() {
+            parts.push(format!("after {}", self.time_start));
+        }
+        if !self.time_end.is_empty() {
+            parts.push(format!("before {}", self.time_end));
+        }
+        parts.join(" · ")
+    }
+
     pub fn store(&self) {
         LocalStorage::set("endpoint", self.endpoint.clone().to_string()).unwrap();
         LocalStorage::set("subreddit", self.subreddit.clone()).unwrap();
@@ -63,7 +87,7 @@ impl SearchParams {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum Endpoint {
     Submission,
     Comment,
@@ -85,15 +109,66 @@ impl Endpoint {
         json: impl AsRef<str>,
         tz_offset: i64,
     ) -> Result<Vec<RedditType>, serde_json::Error> {
-        match self {
-            Self::Submission => RedditSubmission::parse_pushshift(json, tz_offset),
-            Self::Comment => RedditComment::parse_pushshift(json, tz_offset),
-        }
+        self.parse_page(json, tz_offset).map(|p| p.items)
+    }
+
+    /// Parse a response along with its metadata, if the backend sent any
+    pub fn parse_page(
+        &self,
+        json: impl AsRef<str>,
+        tz_offset: i64,
+    ) -> Result<Page, serde_json::Error> {
+        let parsed = match self {
+            Self::Submission => RedditSubmission::parse_pushshift(&json, tz_offset),
+            Self::Comment => RedditComment::parse_pushshift(&json, tz_offset),
+        };
+        // Reddit compatible backends answer with listings
+        parsed.or_else(|e| {
+            let items = listing::parse(json, tz_offset).map_err(|_| e)?;
+            Ok(Page {
+                items,
+                metadata: None,
+                leniency: Leniency::default(),
+            })
+        })
     }
 
     pub fn list() -> Vec<String> {
         vec![COMMENT_STR.into(), SUBMISSION_STR.into()]
     }
+
+    /// Endpoint that returns items like this one, only submissions have titles
+    pub fn detect(item: &serde_json::Value) -> Self {
+        if item.get("title").is_some() {
+            Self::Submission
+        } else {
+            Self::Comment
+        }
+    }
+
+    /// Host serving this endpoint
+    pub fn backend(&self) -> String {
+        url::Url::parse(self.url())
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_owned))
+            .unwrap_or_default()
+    }
+
+    /// Short name used in page URLs
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Submission => "submission",
+            Self::Comment => "comment",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "submission" => Some(Self::Submission),
+            "comment" => Some(Self::Comment),
+            _ => None,
+        }
+    }
 }
 
 impl Display for Endpoint {
@@ -116,3 +191,45 @@ impl FromStr for Endpoint {
         }
     }
 }
+
+static TIME_FORMAT: &str = "[year]-[month]-[day] [hour]:[minute]";
+
+/// Parse a local time as entered in the search form into a unix timestamp
+pub fn parse_time(s: impl AsRef<str>, offset: i64) -> Option<i64> {
+    let ts = parse_local(s)?
+        .assume_offset(UtcOffset::from_whole_seconds(60 * offset as i32).ok()?)
+        .unix_timestamp();
+    Some(ts)
+}
+
+/// Parse a time as entered in the search form, without a timezone
+pub fn parse_local(s: impl AsRef<str>) -> Option<PrimitiveDateTime> {
+    let format = format_description::parse(TIME_FORMAT).unwrap();
+    PrimitiveDateTime::parse(s.as_ref().trim(), &format).ok()
+}
+
+pub fn format_local(time: PrimitiveDateTime) -> String {
+    let format = format_description::parse(TIME_FORMAT).unwrap();
+    time.format(&format).unwrap_or_default()
+}
+
+/// The current local time
+pub fn now_local(offset: i64) -> PrimitiveDateTime {
+    let now = OffsetDateTime::from_unix_timestamp((js_sys::Date::now() / 1000.0) as i64)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH);
+    let now = match UtcOffset::from_whole_seconds(60 * offset as i32) {
+        Ok(offset) => now.to_offset(offset),
+        Err(_) => now,
+    };
+    PrimitiveDateTime::new(now.date(), now.time())
+}
+
+/// Format a unix timestamp as local time for the search form
+pub fn format_time(ts: i64, offset: i64) -> Option<String> {
+    let format = format_description::parse(TIME_FORMAT).unwrap();
+    OffsetDateTime::from_unix_timestamp(ts)
+        .ok()?
+        .to_offset(UtcOffset::from_whole_seconds(60 * offset as i32).ok()?)
+        .format(&format)
+        .ok()
+}
@@ -0,0 +1,191 @@
+use gloo_storage::{LocalStorage, Storage};
+use serde::{Deserialize, Serialize};
+
+use crate::filter::Filters;
+use crate::params::{format_time, parse_time, SearchParams};
+
+static PRESETS_KEY: &str = "presets";
+/// Choice of the preset select that leaves the form as is, no preset can be named this
+pub static NO_PRESET: &str = "(none)";
+
+/// A named search, including its client-side filters
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub params: SearchParams,
+    pub filters: Filters,
+}
+
+/// A preset as exported, with times as unix timestamps so a file means the same
+/// search in every timezone
+#[derive(Serialize, Deserialize)]
+struct ExportedPreset {
+    name: String,
+    params: SearchParams,
+    filters: Filters,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    after: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    before: Option<i64>,
+}
+
+impl ExportedPreset {
+    fn new(preset: &Preset, tz_offset: i64) -> Self {
+        let mut params = preset.params.clone();
+        let after = parse_time(std::mem::take(&mut params.time_start), tz_offset);
+        let before = parse_time(std::mem::take(&mut params.time_end), tz_offset);
+        Self {
+            name: preset.name.clone(),
+            params,
+            filters: preset.filters.clone(),
+            after,
+            before,
+        }
+    }
+
+    /// Files exported before times were timestamps keep their local times
+    fn into_preset(self, tz_offset: i64) -> Preset {
+        let mut params = self.params;
+        let time = |ts: Option<i64>| ts.and_then(|ts| format_time(ts, tz_offset));
+        if let Some(t) = time(self.after) {
+            params.time_start = t;
+        }
+        if let Some(t) = time(self.before) {
+            params.time_end = t;
+        }
+        Preset {
+            name: self.name,
+            params,
+            filters: self.filters,
+        }
+    }
+}
+
+/// Saved searches, persisted in LocalStorage
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Presets {
+    presets: Vec<Preset>,
+}
+
+impl Presets {
+    pub fn load() -> Self {
+        let presets = LocalStorage::get(PRESETS_KEY).unwrap_or_default();
+        Self { presets }
+    }
+
+    pub fn store(&self) {
+        if let Err(e) = LocalStorage::set(PRESETS_KEY, &self.presets) {
+            log::warn!("Unable to store saved searches: {}", e);
+        }
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.presets.iter().map(|p| p.name.clone()).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.presets.iter().find(|p| p.name == name)
+    }
+
+    /// Add a preset, replacing any existing one with the same name
+    pub fn save(&mut self, preset: Preset) {
+        match self.presets.iter_mut().find(|p| p.name == preset.name) {
+            Some(p) => *p = preset,
+            None => {
+                self.presets.push(preset);
+                self.presets.sort_by(|a, b| a.name.cmp(&b.name));
+            }
+        }
+        self.store();
+    }
+
+    pub fn delete(&mut self, name: &str) {
+        self.presets.retain(|p| p.name != name);
+        self.store();
+    }
+
+    /// Whether a preset can't be given this name
+    pub fn is_reserved(name: &str) -> bool {
+        name == NO_PRESET
+    }
+
+    pub fn export(&self, tz_offset: i64) -> String {
+        let presets: Vec<_> = self
+            .presets
+            .iter()
+            .map(|p| ExportedPreset::new(p, tz_offset))
+            .collect();
+        serde_json::to_string_pretty(&presets).unwrap()
+    }
+
+    /// Merge presets from an exported file, returns how many were imported
+    pub fn import(&mut self, json: &str, tz_offset: i64) -> Result<usize, serde_json::Error> {
+        let presets = parse_export(json, tz_offset)?;
+        let count = presets.len();
+        for preset in presets {
+            self.save(preset);
+        }
+        Ok(count)
+    }
+}
+
+/// Presets of an exported file, leaving out ones with a reserved name
+fn parse_export(json: &str, tz_offset: i64) -> Result<Vec<Preset>, serde_json::Error> {
+    let presets: Vec<ExportedPreset> = serde_json::from_str(json)?;
+    Ok(presets
+        .into_iter()
+        .filter(|p| !Presets::is_reserved(&p.name))
+        .map(|p| p.into_preset(tz_offset))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::filter::RemovedFilter;
+    use crate::params::Endpoint;
+
+    fn preset(name: &str, time_start: &str) -> Preset {
+        Preset {
+            name: name.to_owned(),
+            params: SearchParams {
+                endpoint: Endpoint::Comment,
+                subreddit: "rust".to_owned(),
+                author: String::new(),
+                query: String::new(),
+                time_start: time_start.to_owned(),
+                time_end: String::new(),
+            },
+            filters: Filters {
+                removed: RemovedFilter::All,
+            },
+        }
+    }
+
+    #[test]
+    fn times_are_exported_as_timestamps() {
+        let presets = Presets {
+            presets: vec![preset("a", "2023-01-02 03:04")],
+        };
+        let json = presets.export(60);
+        assert!(json.contains("\"after\": 1672625040"));
+
+        // Imported an hour further east
+        let imported = parse_export(&json, 120).unwrap();
+        assert_eq!(imported, [preset("a", "2023-01-02 04:04")]);
+    }
+
+    #[test]
+    fn local_times_of_older_files_are_kept() {
+        let json = serde_json::to_string(&[preset("a", "2023-01-02 03:04")]).unwrap();
+        let imported = parse_export(&json, 120).unwrap();
+        assert_eq!(imported, [preset("a", "2023-01-02 03:04")]);
+    }
+
+    #[test]
+    fn reserved_names_are_not_imported() {
+        let json = serde_json::to_string(&[preset(NO_PRESET, ""), preset("b", "")]).unwrap();
+        let imported = parse_export(&json, 0).unwrap();
+        assert_eq!(imported, [preset("b", "")]);
+    }
+}
@@ -1,36 +1,154 @@
+use std::cell::Cell;
+
 use serde::de::Error;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 use time::{format_description, OffsetDateTime, UtcOffset};
-use web_sys::HtmlImageElement;
+use web_sys::MouseEvent;
 use yew::prelude::*;
 
+use crate::live::LiveState;
+use crate::media::{self, GalleryItem, Media, MediaKind, OnExpand};
+use crate::settings::SensitiveMedia;
+
+// Binary formats, used to talk to the worker, store the plain values.
+// Archives from different years disagree on types and leave out or null fields,
+// so JSON is read leniently.
+
+/// Fallbacks taken while reading JSON leniently, a sign the format changed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Leniency {
+    /// Null fields given their default value
+    pub defaulted: usize,
+    /// Values of an unexpected type that were left out
+    pub skipped: usize,
+}
+
+thread_local! {
+    static LENIENCY: Cell<Leniency> = Cell::new(Leniency::default());
+}
+
+impl Leniency {
+    /// Run a parse and count the fallbacks it took
+    pub fn track<T>(parse: impl FnOnce() -> T) -> (T, Self) {
+        LENIENCY.with(|l| l.set(Self::default()));
+        let result = parse();
+        (result, LENIENCY.with(Cell::take))
+    }
+
+    fn count(update: impl FnOnce(&mut Self)) {
+        LENIENCY.with(|l| {
+            let mut leniency = l.get();
+            update(&mut leniency);
+            l.set(leniency);
+        });
+    }
+
+    pub(crate) fn defaulted() {
+        Self::count(|l| l.defaulted += 1);
+    }
+
+    pub(crate) fn skipped() {
+        Self::count(|l| l.skipped += 1);
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.defaulted > 0 {
+            warnings.push(format!("{} null fields were read as empty", self.defaulted));
+        }
+        if self.skipped > 0 {
+            warnings.push(format!(
+                "{} values of an unexpected type were left out",
+                self.skipped
+            ));
+        }
+        warnings
+    }
+}
+
 fn deserialize_decode_html<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let s: String = Deserialize::deserialize(deserializer)?;
-    let decoded = html_escape::decode_html_entities(&s).into_owned();
+    if !deserializer.is_human_readable() {
+        return String::deserialize(deserializer);
+    }
+    let s: Option<String> = Deserialize::deserialize(deserializer)?;
+    if s.is_none() {
+        Leniency::defaulted();
+    }
+    let decoded = html_escape::decode_html_entities(&s.unwrap_or_default()).into_owned();
     Ok(decoded)
 }
 
+/// Missing and null values become the default
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return T::deserialize(deserializer);
+    }
+    let value: Option<T> = Deserialize::deserialize(deserializer)?;
+    Ok(value.unwrap_or_else(|| {
+        Leniency::defaulted();
+        T::default()
+    }))
+}
+
+/// Some archives store ids with their kind prefix, like `t1_abc`
+fn deserialize_id<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let s = String::deserialize(deserializer)?;
+    Ok(strip_kind(&s).to_owned())
+}
+
+fn strip_kind(id: &str) -> &str {
+    match id.split_once('_') {
+        Some((kind, id)) if kind.len() == 2 && kind.starts_with('t') => id,
+        _ => id,
+    }
+}
+
+/// Archives keep text entity encoded
+fn serialize_encode_html<S: Serializer>(s: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serializer.serialize_str(s);
+    }
+    serializer.serialize_str(&html_escape::encode_text(s))
+}
+
 fn deserialize_link_id<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: Deserializer<'de>,
 {
+    if !deserializer.is_human_readable() {
+        return String::deserialize(deserializer);
+    }
     let s: String = Deserialize::deserialize(deserializer)?;
-    let id = s
-        .split_once('_')
-        .ok_or_else(|| anyhow::anyhow!("Invalid link_id {}", s))
-        .map_err(D::Error::custom)?
-        .1
-        .to_owned();
-    Ok(id)
+    Ok(strip_kind(&s).to_owned())
+}
+
+fn serialize_link_id<S: Serializer>(id: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    if !serializer.is_human_readable() {
+        return serializer.serialize_str(id);
+    }
+    serializer.serialize_str(&format!("t3_{}", id))
 }
 
 fn deserialize_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+    if !deserializer.is_human_readable() {
+        return i64::deserialize(deserializer);
+    }
     let val = match Value::deserialize(deserializer)? {
-        Value::String(s) => s.parse().map_err(D::Error::custom)?,
+        Value::String(s) => match s.parse() {
+            Ok(i) => i,
+            Err(e) => s.parse::<f64>().map_err(|_| D::Error::custom(e))? as i64,
+        },
         Value::Number(num) => {
             if num.is_i64() {
                 num.as_i64().unwrap()
@@ -45,38 +163,202 @@ fn deserialize_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D:
     Ok(val)
 }
 
+/// Whether and when an item was edited, old archives only tell whether it was
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Edited {
+    #[default]
+    No,
+    Yes,
+    At(i64),
+}
+
+/// Archives store `false`, `true` or the time of the edit
+impl Serialize for Edited {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self, serializer.is_human_readable()) {
+            (Self::No, true) => serializer.serialize_bool(false),
+            (Self::Yes, true) => serializer.serialize_bool(true),
+            (Self::At(t), true) => serializer.serialize_i64(*t),
+            (Self::No, false) => serializer.serialize_i64(0),
+            (Self::Yes, false) => serializer.serialize_i64(-1),
+            (Self::At(t), false) => serializer.serialize_i64(*t),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Edited {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if !deserializer.is_human_readable() {
+            return Ok(match i64::deserialize(deserializer)? {
+                0 => Self::No,
+                -1 => Self::Yes,
+                t => Self::At(t),
+            });
+        }
+        Ok(match Value::deserialize(deserializer)? {
+            Value::Bool(true) => Self::Yes,
+            Value::Number(n) => match n.as_f64() {
+                Some(t) if t > 0.0 => Self::At(t as i64),
+                _ => Self::No,
+            },
+            Value::String(s) => match s.parse::<f64>() {
+                Ok(t) if t > 0.0 => Self::At(t as i64),
+                _ => Self::No,
+            },
+            Value::Bool(false) | Value::Null => Self::No,
+            _ => {
+                Leniency::skipped();
+                Self::No
+            }
+        })
+    }
+}
+
+impl Edited {
+    fn html(&self, tz_offset: i64) -> Html {
+        let title = match self {
+            Self::No => return html! {},
+            Self::Yes => "edited".to_owned(),
+            Self::At(t) => format!("edited {}", format_timestamp(*t, tz_offset)),
+        };
+        html! { <div class="time" {title}>{"edited"}</div> }
+    }
+}
+
+/// Bodies longer than this many bytes or lines are collapsed
+const COLLAPSE_LEN: usize = 1500;
+const COLLAPSE_LINES: usize = 20;
+/// Size of the preview shown for collapsed bodies
+const PREVIEW_LEN: usize = 600;
+const PREVIEW_LINES: usize = 8;
+
+/// State from outside of an item that affects how it is rendered
+pub struct CardContext<'a> {
+    pub live: Option<&'a LiveState>,
+    pub on_expand: &'a OnExpand,
+    pub sensitive_media: SensitiveMedia,
+    /// The user clicked to reveal this item's sensitive media
+    pub revealed: bool,
+    pub on_reveal: Callback<()>,
+    /// Long bodies are shown in full
+    pub expanded: bool,
+    pub on_toggle_expanded: Callback<()>,
+    /// Found by watching the search after the results were first shown
+    pub new: bool,
+}
+
 pub trait Reddit {
     fn time(&self) -> i64;
-    fn html(&self) -> Html;
-    fn parse_pushshift(
-        json: impl AsRef<str>,
-        tz_offset: i64,
-    ) -> Result<Vec<RedditType>, serde_json::Error>
+    fn fullname(&self) -> String;
+    fn text(&self) -> &str;
+    fn html(&self, ctx: &CardContext) -> Html;
+    fn parse_pushshift(json: impl AsRef<str>, tz_offset: i64) -> Result<Page, serde_json::Error>
     where
         Self: Sized;
     fn permalink(&self) -> String;
+    fn removal(&self) -> Removal;
 }
 
 #[derive(Deserialize, Debug, Clone)]
 struct RedditMultiple<T> {
     data: Vec<T>,
+    #[serde(default, deserialize_with = "deserialize_metadata")]
+    metadata: Option<Metadata>,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+/// Metadata of an unexpected shape is left out rather than failing the page
+fn deserialize_metadata<'de, D>(deserializer: D) -> Result<Option<Metadata>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        return Option::<Metadata>::deserialize(deserializer);
+    }
+    let value = Value::deserialize(deserializer)?;
+    Ok(serde_json::from_value(value).unwrap_or_else(|_| {
+        Leniency::skipped();
+        None
+    }))
+}
+
+/// A page of results with what the API told about the search
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq)]
+pub struct Page {
+    pub items: Vec<RedditType>,
+    pub metadata: Option<Metadata>,
+    pub leniency: Leniency,
+}
+
+/// Search statistics of Pushshift style APIs, sent when asked for with `metadata=true`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct Metadata {
+    /// Matches of the whole search, not only this page
+    pub total_results: Option<u64>,
+    pub shards: Option<Shards>,
+    pub timed_out: Option<bool>,
+    pub terminated_early: Option<bool>,
+    pub execution_time_milliseconds: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct Shards {
+    pub total: u32,
+    pub successful: u32,
+    pub failed: u32,
+}
+
+impl Metadata {
+    /// Reasons the results may be incomplete
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if let Some(shards) = self.shards.filter(|s| s.failed > 0) {
+            warnings.push(format!(
+                "{} of {} shards failed, results may be missing",
+                shards.failed, shards.total
+            ));
+        }
+        if self.timed_out == Some(true) {
+            warnings.push("The search timed out, results may be missing".to_owned());
+        }
+        if self.terminated_early == Some(true) {
+            warnings.push("The search was cut short, results may be missing".to_owned());
+        }
+        warnings
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RedditComment {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     subreddit: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     author: String,
     #[serde(rename = "created_utc")]
     #[serde(deserialize_with = "deserialize_int")]
     time: i64,
-    #[serde(deserialize_with = "deserialize_decode_html")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_decode_html",
+        serialize_with = "serialize_encode_html"
+    )]
     body: String,
+    #[serde(default)]
     permalink: Option<String>,
     #[serde(skip)]
     tz_offset: i64,
+    #[serde(deserialize_with = "deserialize_id")]
     id: String,
-    #[serde(deserialize_with = "deserialize_link_id")]
+    #[serde(
+        deserialize_with = "deserialize_link_id",
+        serialize_with = "serialize_link_id"
+    )]
     link_id: String,
+    #[serde(default)]
+    removed_by_category: Option<String>,
+    #[serde(default)]
+    edited: Edited,
 }
 
 impl Reddit for RedditComment {
@@ -84,31 +366,46 @@ impl Reddit for RedditComment {
         self.time
     }
 
-    fn html(&self) -> Html {
+    fn fullname(&self) -> String {
+        format!("t1_{}", self.id)
+    }
+
+    fn text(&self) -> &str {
+        &self.body
+    }
+
+    fn html(&self, ctx: &CardContext) -> Html {
+        let live = ctx.live.map(LiveState::html).unwrap_or_default();
         html! {
             <a class="reddit_comment" href={self.permalink()} target="_blank" rel="noopener noreferrer" title="View on Reddit">
                 <div class="comment_header">
                     <div class="subreddit">{String::from("r/") + &self.subreddit}</div>
                     <div class="author">{String::from("u/") + &self.author}</div>
                     <div class="time">{format_timestamp(self.time, self.tz_offset)}</div>
+                    {self.edited.html(self.tz_offset)}
+                    {new_badge(ctx.new)}
                 </div>
-                <div class="comment_body">{self.body.clone()}</div>
+                {self.removal().html()}
+                {live}
+                {body_html(&self.body, ctx)}
             </a>
         }
     }
 
-    fn parse_pushshift(
-        json: impl AsRef<str>,
-        tz_offset: i64,
-    ) -> Result<Vec<RedditType>, serde_json::Error> {
+    fn parse_pushshift(json: impl AsRef<str>, tz_offset: i64) -> Result<Page, serde_json::Error> {
         let comments: RedditMultiple<Self> = serde_json::from_str(json.as_ref())?;
+        let metadata = comments.metadata;
         let mut comments = comments.data;
         for comment in comments.iter_mut() {
             comment.tz_offset = tz_offset;
         }
 
-        let comments = comments.into_iter().map(RedditType::Comment).collect();
-        Ok(comments)
+        let items = comments.into_iter().map(RedditType::Comment).collect();
+        Ok(Page {
+            items,
+            metadata,
+            leniency: Leniency::default(),
+        })
     }
 
     fn permalink(&self) -> String {
@@ -121,26 +418,132 @@ impl Reddit for RedditComment {
             )
         }
     }
+
+    fn removal(&self) -> Removal {
+        Removal::new(
+            &self.author,
+            &self.body,
+            self.removed_by_category.as_deref(),
+        )
+    }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RedditSubmission {
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     subreddit: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     author: String,
     #[serde(rename = "created_utc")]
     #[serde(deserialize_with = "deserialize_int")]
     time: i64,
+    #[serde(default)]
     permalink: Option<String>,
     #[serde(skip)]
     tz_offset: i64,
+    #[serde(deserialize_with = "deserialize_id")]
     id: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     is_self: bool,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     thumbnail: String,
-    #[serde(deserialize_with = "deserialize_decode_html")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_decode_html",
+        serialize_with = "serialize_encode_html"
+    )]
     title: String,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
     url: String,
-    #[serde(deserialize_with = "deserialize_decode_html")]
+    #[serde(
+        default,
+        deserialize_with = "deserialize_decode_html",
+        serialize_with = "serialize_encode_html"
+    )]
     selftext: String,
+    #[serde(default)]
+    removed_by_category: Option<String>,
+    #[serde(default)]
+    edited: Edited,
+    #[serde(
+        default,
+        deserialize_with = "media::deserialize_media_metadata",
+        serialize_with = "media::serialize_media_metadata"
+    )]
+    media_metadata: Vec<(String, Media)>,
+    #[serde(
+        default,
+        deserialize_with = "media::deserialize_gallery_data",
+        serialize_with = "media::serialize_gallery_data"
+    )]
+    gallery_data: Vec<GalleryItem>,
+    #[serde(
+        default,
+        deserialize_with = "media::deserialize_preview",
+        serialize_with = "media::serialize_preview"
+    )]
+    preview: Option<Box<Media>>,
+    #[serde(
+        default,
+        deserialize_with = "media::deserialize_secure_media",
+        serialize_with = "media::serialize_secure_media"
+    )]
+    secure_media: Option<Box<Media>>,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    over_18: bool,
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    spoiler: bool,
+}
+
+impl RedditSubmission {
+    /// Why this submission's media should not be shown right away
+    fn sensitive_label(&self) -> Option<&'static str> {
+        match (self.over_18, self.spoiler) {
+            (true, true) => Some("NSFW spoiler"),
+            (true, false) => Some("NSFW"),
+            (false, true) => Some("Spoiler"),
+            (false, false) => None,
+        }
+    }
+
+    /// Images and videos of the submission, in display order
+    pub fn media(&self) -> Vec<Media> {
+        if !self.gallery_data.is_empty() {
+            return self
+                .gallery_data
+                .iter()
+                .filter_map(|item| {
+                    let (_, m) = self
+                        .media_metadata
+                        .iter()
+                        .find(|(id, _)| id == &item.media_id)?;
+                    Some(Media {
+                        caption: item.caption.clone(),
+                        ..m.clone()
+                    })
+                })
+                .collect();
+        }
+
+        match (&self.secure_media, &self.preview) {
+            (Some(m), preview) if m.kind == MediaKind::Video => {
+                let poster = preview.as_ref().map(|p| p.preview.clone());
+                vec![Media {
+                    preview: poster.unwrap_or_default(),
+                    ..m.as_ref().clone()
+                }]
+            }
+            (_, Some(p)) => vec![p.as_ref().clone()],
+            (Some(m), None) => vec![m.as_ref().clone()],
+            (None, None) if media::is_image_url(&self.url) => vec![Media {
+                kind: MediaKind::Image,
+                url: self.url.clone(),
+                preview: self.url.clone(),
+                caption: None,
+            }],
+            (None, None) => Vec::new(),
+        }
+    }
 }
 
 impl Reddit for RedditSubmission {
@@ -148,32 +551,54 @@ impl Reddit for RedditSubmission {
         self.time
     }
 
-    fn html(&self) -> Html {
+    fn fullname(&self) -> String {
+        format!("t3_{}", self.id)
+    }
+
+    fn text(&self) -> &str {
+        if self.is_self {
+            &self.selftext
+        } else {
+            ""
+        }
+    }
+
+    fn html(&self, ctx: &CardContext) -> Html {
+        let live = ctx.live.map(LiveState::html).unwrap_or_default();
         let selftext = if self.is_self {
-            html! {
-                <div class="comment_body">{self.selftext.clone()}</div>
-            }
+            body_html(&self.selftext, ctx)
         } else {
             html! {}
         };
 
-        let thumbnail = if !self.is_self && self.thumbnail != "default" {
-            let onerror = Callback::from(|e: Event| {
-                if let Some(target) = e.target_dyn_into::<HtmlImageElement>() {
-                    static BAD_IMAGE: &str = "bad-image.svg";
-                    if !target.src().ends_with(BAD_IMAGE) {
-                        target.set_src(BAD_IMAGE);
-                    }
-                }
-            });
-            html! {
+        // Sensitive media is blurred or hidden until revealed
+        let label = if ctx.revealed {
+            None
+        } else {
+            self.sensitive_label()
+        };
+        let sensitive = |content: Html| {
+            media::sensitive_html(content, label, ctx.sensitive_media, ctx.on_reveal.clone())
+        };
+
+        // Prefer full media previews, fall back to the thumbnail for other links
+        let media = self.media();
+        let (thumbnail, media) = if !media.is_empty() {
+            (
+                html! {},
+                sensitive(media::media_html(&media, ctx.on_expand)),
+            )
+        } else if !self.is_self && self.thumbnail.starts_with("http") {
+            let thumbnail = html! {
                 <img class="post_thumb"
                     alt="Reddit thumbnail"
+                    loading="lazy"
                     src={self.thumbnail.clone()}
-                    onerror={onerror} />
-            }
+                    onerror={media::bad_image_onerror()} />
+            };
+            (sensitive(thumbnail), html! {})
         } else {
-            html! {}
+            (html! {}, html! {})
         };
 
         html! {
@@ -182,7 +607,11 @@ impl Reddit for RedditSubmission {
                     <div class="subreddit">{String::from("r/") + &self.subreddit}</div>
                     <div class="author">{String::from("u/") + &self.author}</div>
                     <div class="time">{format_timestamp(self.time, self.tz_offset)}</div>
+                    {self.edited.html(self.tz_offset)}
+                    {new_badge(ctx.new)}
                 </div>
+                {self.removal().html()}
+                {live}
                 <div class="post">
                     <div>
                         {thumbnail}
@@ -192,25 +621,28 @@ impl Reddit for RedditSubmission {
                         {selftext}
                     </div>
                 </div>
+                {media}
             </a>
         }
     }
 
-    fn parse_pushshift(
-        json: impl AsRef<str>,
-        tz_offset: i64,
-    ) -> Result<Vec<RedditType>, serde_json::Error> {
+    fn parse_pushshift(json: impl AsRef<str>, tz_offset: i64) -> Result<Page, serde_json::Error> {
         let submissions: RedditMultiple<Self> = serde_json::from_str(json.as_ref())?;
+        let metadata = submissions.metadata;
         let mut submissions = submissions.data;
         for submission in submissions.iter_mut() {
             submission.tz_offset = tz_offset;
         }
 
-        let submissions = submissions
+        let items = submissions
             .into_iter()
             .map(RedditType::Submission)
             .collect();
-        Ok(submissions)
+        Ok(Page {
+            items,
+            metadata,
+            leniency: Leniency::default(),
+        })
     }
 
     fn permalink(&self) -> String {
@@ -223,15 +655,89 @@ impl Reddit for RedditSubmission {
             )
         }
     }
+
+    fn removal(&self) -> Removal {
+        Removal::new(
+            &self.author,
+            self.text(),
+            self.removed_by_category.as_deref(),
+        )
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 pub enum RedditType {
     Comment(RedditComment),
     Submission(RedditSubmission),
 }
 
+/// Binary form of `RedditType`, which keeps the kind and time zone
+#[derive(Serialize)]
+enum TaggedRef<'a> {
+    Comment(i64, &'a RedditComment),
+    Submission(i64, &'a RedditSubmission),
+}
+
+#[derive(Deserialize)]
+enum Tagged {
+    Comment(i64, RedditComment),
+    Submission(i64, RedditSubmission),
+}
+
+/// Human readable formats use the archive's format for either kind
+impl Serialize for RedditType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match (self, serializer.is_human_readable()) {
+            (Self::Comment(c), true) => c.serialize(serializer),
+            (Self::Submission(s), true) => s.serialize(serializer),
+            (Self::Comment(c), false) => TaggedRef::Comment(c.tz_offset, c).serialize(serializer),
+            (Self::Submission(s), false) => {
+                TaggedRef::Submission(s.tz_offset, s).serialize(serializer)
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RedditType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        if deserializer.is_human_readable() {
+            let value = Value::deserialize(deserializer)?;
+            return if value.get("title").is_some() {
+                serde_json::from_value(value).map(Self::Submission)
+            } else {
+                serde_json::from_value(value).map(Self::Comment)
+            }
+            .map_err(D::Error::custom);
+        }
+
+        Ok(match Tagged::deserialize(deserializer)? {
+            Tagged::Comment(tz_offset, c) => Self::Comment(RedditComment { tz_offset, ..c }),
+            Tagged::Submission(tz_offset, s) => {
+                Self::Submission(RedditSubmission { tz_offset, ..s })
+            }
+        })
+    }
+}
+
 impl RedditType {
+    /// Read a comment from its JSON
+    pub fn comment(data: Value, tz_offset: i64) -> Result<Self, serde_json::Error> {
+        let comment: RedditComment = serde_json::from_value(data)?;
+        Ok(Self::Comment(RedditComment {
+            tz_offset,
+            ..comment
+        }))
+    }
+
+    /// Read a submission from its JSON
+    pub fn submission(data: Value, tz_offset: i64) -> Result<Self, serde_json::Error> {
+        let submission: RedditSubmission = serde_json::from_value(data)?;
+        Ok(Self::Submission(RedditSubmission {
+            tz_offset,
+            ..submission
+        }))
+    }
+
     pub fn time(&self) -> i64 {
         match self {
             Self::Comment(c) => c.time(),
@@ -239,15 +745,238 @@ impl RedditType {
         }
     }
 
-    pub fn html(&self) -> Html {
+    pub fn fullname(&self) -> String {
+        match self {
+            Self::Comment(c) => c.fullname(),
+            Self::Submission(s) => s.fullname(),
+        }
+    }
+
+    pub fn html(&self, ctx: &CardContext) -> Html {
+        match self {
+            Self::Comment(c) => c.html(ctx),
+            Self::Submission(s) => s.html(ctx),
+        }
+    }
+
+    pub fn removal(&self) -> Removal {
+        match self {
+            Self::Comment(c) => c.removal(),
+            Self::Submission(s) => s.removal(),
+        }
+    }
+
+    pub fn subreddit(&self) -> &str {
+        match self {
+            Self::Comment(c) => &c.subreddit,
+            Self::Submission(s) => &s.subreddit,
+        }
+    }
+
+    pub fn author(&self) -> &str {
+        match self {
+            Self::Comment(c) => &c.author,
+            Self::Submission(s) => &s.author,
+        }
+    }
+
+    /// Title of submissions, comments don't have one
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            Self::Comment(_) => None,
+            Self::Submission(s) => Some(&s.title),
+        }
+    }
+
+    /// Body of comments and selftext of self posts, the parts that can be edited
+    pub fn text(&self) -> &str {
+        match self {
+            Self::Comment(c) => c.text(),
+            Self::Submission(s) => s.text(),
+        }
+    }
+
+    /// Body of comments, selftext of self posts and link of link posts
+    pub fn content(&self) -> &str {
+        match self {
+            Self::Comment(c) => &c.body,
+            Self::Submission(s) if s.is_self => &s.selftext,
+            Self::Submission(s) => &s.url,
+        }
+    }
+
+    pub fn permalink(&self) -> String {
+        match self {
+            Self::Comment(c) => c.permalink(),
+            Self::Submission(s) => s.permalink(),
+        }
+    }
+
+    pub fn edited(&self) -> Edited {
         match self {
-            Self::Comment(c) => c.html(),
-            Self::Submission(s) => s.html(),
+            Self::Comment(c) => c.edited,
+            Self::Submission(s) => s.edited,
+        }
+    }
+}
+
+/// Who took down a body, as indicated by Reddit's placeholder text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemovedBy {
+    /// `[deleted]`, the author deleted it
+    Author,
+    /// `[removed]`, moderators, admins or spam filters removed it
+    Moderator,
+}
+
+impl RemovedBy {
+    fn from_placeholder(s: &str) -> Option<Self> {
+        match s.trim() {
+            "[deleted]" => Some(Self::Author),
+            "[removed]" => Some(Self::Moderator),
+            _ => None,
+        }
+    }
+}
+
+/// Deletion and removal state of a comment or submission
+///
+/// The archive may have captured an item before or after it was taken down.
+/// When `body` is set the text was already gone at archive time, otherwise
+/// any other removal marker means the archived copy still holds the text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Removal {
+    /// Author account shows as `[deleted]`
+    pub author_deleted: bool,
+    /// Body or selftext was a placeholder in the archived copy
+    pub body: Option<RemovedBy>,
+    /// Reddit's `removed_by_category` field
+    pub category: Option<String>,
+}
+
+impl Removal {
+    pub fn new(author: &str, body: &str, category: Option<&str>) -> Self {
+        Self {
+            author_deleted: author == "[deleted]",
+            body: RemovedBy::from_placeholder(body),
+            category: category.filter(|c| !c.is_empty()).map(str::to_owned),
+        }
+    }
+
+    pub fn is_removed(&self) -> bool {
+        self.author_deleted || self.body.is_some() || self.category.is_some()
+    }
+
+    /// Text was taken down, but the archive captured it beforehand
+    pub fn is_preserved(&self) -> bool {
+        self.body.is_none() && (self.author_deleted || self.category.is_some())
+    }
+
+    pub fn html(&self) -> Html {
+        if !self.is_removed() {
+            return html! {};
+        }
+
+        let mut badges = Vec::new();
+        match self.body {
+            Some(RemovedBy::Author) => badges.push(("badge_deleted", "deleted".to_owned())),
+            Some(RemovedBy::Moderator) => badges.push(("badge_removed", "removed".to_owned())),
+            None => {}
+        }
+        if let Some(c) = &self.category {
+            badges.push(("badge_removed", category_text(c)));
+        }
+        if self.author_deleted {
+            badges.push(("badge_deleted", "author deleted".to_owned()));
+        }
+        if self.is_preserved() {
+            badges.push(("badge_preserved", "archived before removal".to_owned()));
+        } else if self.body.is_some() {
+            badges.push(("badge_lost", "text not archived".to_owned()));
+        }
+
+        html! {
+            <div class="badges">
+                {for badges.into_iter().map(|(class, text)| html! {
+                    <span class={classes!("badge", class)}>{text}</span>
+                })}
+            </div>
         }
     }
 }
 
-fn format_timestamp(ts: i64, tz_offset: i64) -> String {
+fn category_text(category: &str) -> String {
+    match category {
+        "deleted" | "author" => "deleted by author".to_owned(),
+        "moderator" => "removed by moderators".to_owned(),
+        "automod_filtered" => "filtered by AutoModerator".to_owned(),
+        "reddit" | "anti_evil_ops" | "community_ops" => "removed by Reddit".to_owned(),
+        "copyright_takedown" => "copyright takedown".to_owned(),
+        "content_takedown" | "legal_operations" => "legal takedown".to_owned(),
+        x => format!("removed ({})", x.replace('_', " ")),
+    }
+}
+
+/// Start of a long text to show while it is collapsed, `None` if it is short
+fn collapsed_preview(text: &str) -> Option<&str> {
+    if text.len() <= COLLAPSE_LEN && text.lines().count() <= COLLAPSE_LINES {
+        return None;
+    }
+
+    let mut end = text.len().min(PREVIEW_LEN);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if let Some((i, _)) = text.match_indices('\n').nth(PREVIEW_LINES - 1) {
+        end = end.min(i);
+    }
+    Some(text[..end].trim_end())
+}
+
+fn body_html(text: &str, ctx: &CardContext) -> Html {
+    let preview = match collapsed_preview(text) {
+        Some(p) => p,
+        None => {
+            return html! {
+                <div class="comment_body">{text}</div>
+            }
+        }
+    };
+
+    let on_toggle = ctx.on_toggle_expanded.clone();
+    let onclick = Callback::from(move |e: MouseEvent| {
+        // Cards are links to Reddit
+        e.prevent_default();
+        e.stop_propagation();
+        on_toggle.emit(());
+    });
+
+    if ctx.expanded {
+        html! {
+            <>
+                <div class="comment_body">{text}</div>
+                <button class="show_more" {onclick}>{"Show less"}</button>
+            </>
+        }
+    } else {
+        html! {
+            <>
+                <div class="comment_body collapsed">{preview}{"…"}</div>
+                <button class="show_more" {onclick}>{"Show more"}</button>
+            </>
+        }
+    }
+}
+
+fn new_badge(new: bool) -> Html {
+    if new {
+        html! { <span class="badge badge_new">{"new"}</span> }
+    } else {
+        html! {}
+    }
+}
+
+pub fn format_timestamp(ts: i64, tz_offset: i64) -> String {
     let dt = OffsetDateTime::from_unix_timestamp(ts)
         .unwrap()
         .to_offset(UtcOffset::from_whole_seconds(tz_offset as i32 * 60).unwrap());
@@ -255,3 +984,16 @@ fn format_timestamp(ts: i64, tz_offset: i64) -> String {
         format_description::parse("[year]-[month]-[day] [hour]:[minute]:[second]").unwrap();
     dt.format(&format).unwrap()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_of_unexpected_shape() {
+        let json = r#"{"data":[],"metadata":{"total_results":3,"shards":"all"}}"#;
+        let (page, leniency) = Leniency::track(|| RedditComment::parse_pushshift(json, 0).unwrap());
+        assert_eq!(page.metadata, None);
+        assert_eq!(leniency.skipped, 1);
+    }
+}
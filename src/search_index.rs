@@ -0,0 +1,491 @@
+use std::cmp::Ordering;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use rust_stemmers::{Algorithm, Stemmer};
+
+use crate::pushshift::RedditType;
+
+/// BM25 term frequency saturation and length normalization
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// Where a term occurs in a document
+struct Posting {
+    doc: u32,
+    positions: Vec<u32>,
+}
+
+/// Full-text index over the title and text of collected results
+///
+/// Documents are only ever appended, so postings stay sorted by document.
+pub struct SearchIndex {
+    stemmer: Stemmer,
+    docs: Vec<Rc<RedditType>>,
+    lengths: Vec<u32>,
+    /// Document of each fullname, so items are only indexed once
+    ids: HashMap<String, u32>,
+    postings: HashMap<String, Vec<Posting>>,
+    total_length: u64,
+}
+
+/// A parsed search
+#[derive(Debug, PartialEq)]
+enum Query {
+    Term(String),
+    Phrase(Vec<String>),
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+impl Default for SearchIndex {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchIndex {
+    pub fn new() -> Self {
+        Self {
+            stemmer: Stemmer::create(Algorithm::English),
+            docs: Vec::new(),
+            lengths: Vec::new(),
+            ids: HashMap::new(),
+            postings: HashMap::new(),
+            total_length: 0,
+        }
+    }
+
+    /// Number of indexed documents
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// Add an item, unless it was added before
+    pub fn add(&mut self, item: Rc<RedditType>) {
+        let doc = self.docs.len() as u32;
+        match self.ids.entry(item.fullname()) {
+            Entry::Occupied(_) => return,
+            Entry::Vacant(entry) => entry.insert(doc),
+        };
+
+        let text = format!("{} {}", item.title().unwrap_or_default(), item.content());
+        let tokens = self.tokenize(&text);
+        for (position, token) in tokens.iter().enumerate() {
+            let postings = self.postings.entry(token.clone()).or_default();
+            match postings.last_mut() {
+                Some(p) if p.doc == doc => p.positions.push(position as u32),
+                _ => postings.push(Posting {
+                    doc,
+                    positions: vec![position as u32],
+                }),
+            }
+        }
+        self.lengths.push(tokens.len() as u32);
+        self.total_length += tokens.len() as u64;
+        self.docs.push(item);
+    }
+
+    pub fn extend<'a>(&mut self, items: impl IntoIterator<Item = &'a Rc<RedditType>>) {
+        for item in items {
+            self.add(item.clone());
+        }
+    }
+
+    /// Lowercased, stemmed words of a text
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split(|c: char| !c.is_alphanumeric() && c != '\'' && c != '’')
+            .map(|w| w.replace(['\'', '’'], "").to_lowercase())
+            .filter(|w| !w.is_empty())
+            .map(|w| self.stemmer.stem(&w).into_owned())
+            .collect()
+    }
+
+    /// Items matching the query, most relevant first
+    ///
+    /// Words must all occur unless joined with `OR`, `"quotes"` match a phrase,
+    /// `-word` or `NOT word` excludes and parentheses group.
+    pub fn search(&self, query: &str) -> Vec<Rc<RedditType>> {
+        let query = match self.parse(query) {
+            Some(q) => q,
+            None => return Vec::new(),
+        };
+
+        let mut terms = Vec::new();
+        scored_terms(&query, &mut terms);
+        terms.sort();
+        terms.dedup();
+
+        let mut hits: Vec<(u32, f64)> = self
+            .matches(&query)
+            .into_iter()
+            .map(|doc| (doc, self.score(doc, &terms)))
+            .collect();
+        hits.sort_by(|(a, a_score), (b, b_score)| {
+            b_score
+                .partial_cmp(a_score)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| {
+                    self.docs[*b as usize]
+                        .time()
+                        .cmp(&self.docs[*a as usize].time())
+                })
+        });
+        hits.into_iter()
+            .map(|(doc, _)| self.docs[doc as usize].clone())
+            .collect()
+    }
+
+    fn parse(&self, query: &str) -> Option<Query> {
+        let tokens = lex(query);
+        let mut parser = Parser {
+            index: self,
+            tokens: &tokens,
+            pos: 0,
+        };
+        let mut parts = Vec::new();
+        // Stray closing parentheses are skipped
+        while parser.pos < tokens.len() {
+            parts.extend(parser.or());
+            parser.pos += 1;
+        }
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Query::And(parts)),
+        }
+    }
+
+    fn postings(&self, term: &str) -> &[Posting] {
+        self.postings
+            .get(term)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// Sorted documents matching a query
+    fn matches(&self, query: &Query) -> Vec<u32> {
+        match query {
+            Query::Term(t) => self.postings(t).iter().map(|p| p.doc).collect(),
+            Query::Phrase(terms) => self.phrase(terms),
+            Query::And(parts) => {
+                let (negated, positive): (Vec<_>, Vec<_>) =
+                    parts.iter().partition(|q| matches!(q, Query::Not(_)));
+                let mut docs = match positive.split_first() {
+                    Some((first, rest)) => rest.iter().fold(self.matches(first), |docs, q| {
+                        intersect(&docs, &self.matches(q))
+                    }),
+                    None => (0..self.docs.len() as u32).collect(),
+                };
+                for q in negated {
+                    if let Query::Not(q) = q {
+                        docs = subtract(&docs, &self.matches(q));
+                    }
+                }
+                docs
+            }
+            Query::Or(parts) => {
+                let mut docs: Vec<u32> = parts.iter().flat_map(|q| self.matches(q)).collect();
+                docs.sort_unstable();
+                docs.dedup();
+                docs
+            }
+            Query::Not(q) => {
+                let all: Vec<u32> = (0..self.docs.len() as u32).collect();
+                subtract(&all, &self.matches(q))
+            }
+        }
+    }
+
+    /// Documents with the terms next to each other in order
+    fn phrase(&self, terms: &[String]) -> Vec<u32> {
+        let lists: Vec<&[Posting]> = terms.iter().map(|t| self.postings(t)).collect();
+        let (first, rest) = match lists.split_first() {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        first
+            .iter()
+            .filter_map(|start| {
+                let following: Option<Vec<&Posting>> =
+                    rest.iter().map(|l| find(l, start.doc)).collect();
+                let following = following?;
+                let found = start.positions.iter().any(|p| {
+                    following
+                        .iter()
+                        .enumerate()
+                        .all(|(i, f)| f.positions.binary_search(&(p + i as u32 + 1)).is_ok())
+                });
+                found.then_some(start.doc)
+            })
+            .collect()
+    }
+
+    fn score(&self, doc: u32, terms: &[&str]) -> f64 {
+        let count = self.docs.len() as f64;
+        let average = self.total_length as f64 / count.max(1.0);
+        let length = self.lengths[doc as usize] as f64;
+
+        terms
+            .iter()
+            .map(|t| {
+                let postings = self.postings(t);
+                let tf = match find(postings, doc) {
+                    Some(p) => p.positions.len() as f64,
+                    None => return 0.0,
+                };
+                let df = postings.len() as f64;
+                let idf = (1.0 + (count - df + 0.5) / (df + 0.5)).ln();
+                idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length / average.max(1.0)))
+            })
+            .sum()
+    }
+}
+
+/// Terms that add to the relevance of a match
+fn scored_terms<'a>(query: &'a Query, terms: &mut Vec<&'a str>) {
+    match query {
+        Query::Term(t) => terms.push(t),
+        Query::Phrase(ts) => terms.extend(ts.iter().map(String::as_str)),
+        Query::And(parts) | Query::Or(parts) => {
+            for q in parts {
+                scored_terms(q, terms);
+            }
+        }
+        Query::Not(_) => {}
+    }
+}
+
+fn find(postings: &[Posting], doc: u32) -> Option<&Posting> {
+    postings
+        .binary_search_by_key(&doc, |p| p.doc)
+        .ok()
+        .map(|i| &postings[i])
+}
+
+fn intersect(a: &[u32], b: &[u32]) -> Vec<u32> {
+    a.iter()
+        .copied()
+        .filter(|d| b.binary_search(d).is_ok())
+        .collect()
+}
+
+fn subtract(a: &[u32], b: &[u32]) -> Vec<u32> {
+    a.iter()
+        .copied()
+        .filter(|d| b.binary_search(d).is_err())
+        .collect()
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    Open,
+    Close,
+    Minus,
+}
+
+fn lex(query: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = query.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            '"' => {
+                chars.next();
+                // An unterminated phrase runs to the end
+                let phrase: String = chars.by_ref().take_while(|c| *c != '"').collect();
+                tokens.push(Token::Phrase(phrase));
+            }
+            '(' | ')' => {
+                chars.next();
+                tokens.push(if c == '(' { Token::Open } else { Token::Close });
+            }
+            '-' => {
+                chars.next();
+                tokens.push(Token::Minus);
+            }
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '"' | '(' | ')') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    tokens
+}
+
+/// Recursive descent over query tokens, `OR` binds looser than `AND`
+struct Parser<'a> {
+    index: &'a SearchIndex,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek_word(&self, word: &str) -> bool {
+        matches!(self.tokens.get(self.pos), Some(Token::Word(w)) if w == word)
+    }
+
+    fn or(&mut self) -> Option<Query> {
+        let mut parts: Vec<Query> = self.and().into_iter().collect();
+        while self.peek_word("OR") {
+            self.pos += 1;
+            parts.extend(self.and());
+        }
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Query::Or(parts)),
+        }
+    }
+
+    fn and(&mut self) -> Option<Query> {
+        let mut parts = Vec::new();
+        loop {
+            match self.tokens.get(self.pos) {
+                None | Some(Token::Close) => break,
+                Some(Token::Word(w)) if w == "OR" => break,
+                Some(Token::Word(w)) if w == "AND" => self.pos += 1,
+                _ => parts.extend(self.unary()),
+            }
+        }
+        match parts.len() {
+            0 => None,
+            1 => parts.pop(),
+            _ => Some(Query::And(parts)),
+        }
+    }
+
+    fn unary(&mut self) -> Option<Query> {
+        let token = self.tokens.get(self.pos)?;
+        self.pos += 1;
+        match token {
+            Token::Minus => self.unary().map(|q| Query::Not(Box::new(q))),
+            Token::Word(w) if w == "NOT" => self.unary().map(|q| Query::Not(Box::new(q))),
+            Token::Open => {
+                let q = self.or();
+                if matches!(self.tokens.get(self.pos), Some(Token::Close)) {
+                    self.pos += 1;
+                }
+                q
+            }
+            Token::Word(text) | Token::Phrase(text) => {
+                let mut terms = self.index.tokenize(text);
+                match terms.len() {
+                    0 => None,
+                    1 => terms.pop().map(Query::Term),
+                    _ => Some(Query::Phrase(terms)),
+                }
+            }
+            Token::Close => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn comment(id: &str, body: &str, time: i64) -> Rc<RedditType> {
+        let data = json!({
+            "id": id,
+            "author": "someone",
+            "body": body,
+            "created_utc": time,
+            "link_id": "t3_abc",
+            "subreddit": "rust",
+        });
+        Rc::new(RedditType::comment(data, 0).unwrap())
+    }
+
+    fn index(bodies: &[&str]) -> SearchIndex {
+        let mut index = SearchIndex::new();
+        for (i, body) in bodies.iter().enumerate() {
+            index.add(comment(&format!("c{}", i), body, i as i64));
+        }
+        index
+    }
+
+    fn ids(index: &SearchIndex, query: &str) -> Vec<String> {
+        let mut ids: Vec<String> = index.search(query).iter().map(|r| r.fullname()).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        let index = index(&["apple banana", "cherry", "apple cherry", "banana"]);
+        let term = |w: &str| Query::Term(w.to_owned());
+        assert_eq!(
+            index.parse("apple banana OR cherry"),
+            Some(Query::Or(vec![
+                Query::And(vec![term("appl"), term("banana")]),
+                term("cherri"),
+            ]))
+        );
+        assert_eq!(
+            ids(&index, "apple banana OR cherry"),
+            ["t1_c0", "t1_c1", "t1_c2"]
+        );
+        assert_eq!(ids(&index, "apple (banana OR cherry)"), ["t1_c0", "t1_c2"]);
+    }
+
+    #[test]
+    fn phrase_needs_adjacent_terms_in_order() {
+        let index = index(&["the quick brown fox", "brown quick fox", "quick and brown"]);
+        assert_eq!(ids(&index, "\"quick brown\""), ["t1_c0"]);
+        assert_eq!(ids(&index, "quick brown"), ["t1_c0", "t1_c1", "t1_c2"]);
+    }
+
+    #[test]
+    fn negation_only_excludes_from_everything() {
+        let index = index(&["rust", "python", "rust and python"]);
+        assert_eq!(ids(&index, "-rust"), ["t1_c1"]);
+        assert_eq!(ids(&index, "NOT python"), ["t1_c0"]);
+        assert_eq!(ids(&index, "python -rust"), ["t1_c1"]);
+    }
+
+    #[test]
+    fn ranks_by_relevance_then_recency() {
+        let index = index(&[
+            "borrow checker",
+            "borrow borrow borrow",
+            "a long text that mentions borrow only once among many other words",
+            "borrow checker",
+        ]);
+        let ranked: Vec<String> = index
+            .search("borrow")
+            .iter()
+            .map(|r| r.fullname())
+            .collect();
+        assert_eq!(ranked, ["t1_c1", "t1_c3", "t1_c0", "t1_c2"]);
+    }
+
+    #[test]
+    fn duplicates_are_indexed_once() {
+        let mut index = index(&["first", "second"]);
+        index.add(comment("c0", "first again", 5));
+        assert_eq!(index.len(), 2);
+        assert_eq!(ids(&index, "first"), ["t1_c0"]);
+        assert_eq!(ids(&index, "second"), ["t1_c1"]);
+    }
+}
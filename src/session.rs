@@ -0,0 +1,105 @@
+use std::rc::Rc;
+
+use js_sys::Uint8Array;
+use miniz_oxide::deflate::compress_to_vec;
+use miniz_oxide::inflate::decompress_to_vec;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use wasm_bindgen::JsCast;
+use web_sys::IdbTransactionMode;
+
+use crate::idb::{complete, describe, open, wait, SESSION_STORE};
+use crate::params::SearchParams;
+use crate::pushshift::RedditType;
+
+/// Key of the one snapshot that is kept
+static KEY: &str = "last";
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// Results of the last search, to bring back after the tab was closed or reloaded
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub params: SearchParams,
+    /// Items in the archive's JSON format
+    results: Vec<Value>,
+    pub exhausted: bool,
+    pub queries: Vec<(String, i64)>,
+    pub scroll_y: f64,
+    /// Unix time the snapshot was taken
+    pub saved: i64,
+}
+
+impl Session {
+    pub fn new(
+        params: SearchParams,
+        results: &[Rc<RedditType>],
+        exhausted: bool,
+        queries: Vec<(String, i64)>,
+        scroll_y: f64,
+    ) -> Self {
+        Self {
+            params,
+            results: results
+                .iter()
+                .map(|r| serde_json::to_value(r.as_ref()).unwrap())
+                .collect(),
+            exhausted,
+            queries,
+            scroll_y,
+            saved: (js_sys::Date::now() / 1000.0) as i64,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Take out the results as a JSON array, for the worker to parse
+    pub fn take_results(&mut self) -> String {
+        serde_json::to_string(&std::mem::take(&mut self.results)).unwrap()
+    }
+}
+
+/// Replace the stored snapshot
+pub async fn save(session: &Session) -> Result<(), String> {
+    let json = serde_json::to_vec(session).map_err(|e| e.to_string())?;
+    let data = Uint8Array::from(compress_to_vec(&json, COMPRESSION_LEVEL).as_slice());
+
+    let db = open().await.map_err(describe)?;
+    let tx = db
+        .transaction_with_str_and_mode(SESSION_STORE, IdbTransactionMode::Readwrite)
+        .map_err(describe)?;
+    let store = tx.object_store(SESSION_STORE).map_err(describe)?;
+    store.put_with_key(&data, &KEY.into()).map_err(describe)?;
+    complete(&tx).await.map_err(describe)
+}
+
+pub async fn load() -> Result<Option<Session>, String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db.transaction_with_str(SESSION_STORE).map_err(describe)?;
+    let store = tx.object_store(SESSION_STORE).map_err(describe)?;
+    let request = store.get(&KEY.into()).map_err(describe)?;
+    let data = match wait(&request).await.map_err(describe)? {
+        data if data.is_undefined() => return Ok(None),
+        data => data.unchecked_into::<Uint8Array>().to_vec(),
+    };
+
+    let json = decompress_to_vec(&data).map_err(|e| format!("unable to decompress: {:?}", e))?;
+    serde_json::from_slice(&json)
+        .map(Some)
+        .map_err(|e| e.to_string())
+}
+
+pub async fn clear() -> Result<(), String> {
+    let db = open().await.map_err(describe)?;
+    let tx = db
+        .transaction_with_str_and_mode(SESSION_STORE, IdbTransactionMode::Readwrite)
+        .map_err(describe)?;
+    let store = tx.object_store(SESSION_STORE).map_err(describe)?;
+    store.delete(&KEY.into()).map_err(describe)?;
+    complete(&tx).await.map_err(describe)
+}
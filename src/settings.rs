@@ -0,0 +1,136 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use gloo_storage::{LocalStorage, Storage};
+
+static DEFAULT_LIVE_API: &str = "https://www.reddit.com/api/info.json";
+/// Watch intervals in seconds, shorter ones would hammer the API
+const DEFAULT_WATCH_INTERVAL: u32 = 60;
+pub const MIN_WATCH_INTERVAL: u32 = 10;
+/// A day, longer ones are clamped
+pub const MAX_WATCH_INTERVAL: u32 = 24 * 60 * 60;
+
+/// User preferences that are not part of a search
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Settings {
+    /// Reddit `/api/info` compatible endpoint used to check live state
+    pub live_api: String,
+    /// How media of NSFW and spoiler submissions is shown
+    pub sensitive_media: SensitiveMedia,
+    /// Only render results near the viewport, breaks find in page
+    pub virtualize: bool,
+    /// Fetch the next page when scrolling to the bottom of the results
+    pub infinite_scroll: bool,
+    /// Seconds between checks for new results of a watched search
+    pub watch_interval: u32,
+    /// Raise a browser notification when a watched search has new results
+    pub watch_notify: bool,
+    /// Keep everything fetched in a local corpus in IndexedDB
+    pub corpus: bool,
+    /// Search the local corpus instead of the API
+    pub corpus_only: bool,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let live_api = match LocalStorage::get("live_api") {
+            Ok(s) => s,
+            Err(_) => DEFAULT_LIVE_API.to_owned(),
+        };
+
+        let sensitive_media = match LocalStorage::get("sensitive_media")
+            .map(|s: String| SensitiveMedia::from_str(s.as_str()))
+        {
+            Ok(Ok(m)) => m,
+            _ => SensitiveMedia::Blur,
+        };
+
+        let virtualize = LocalStorage::get("virtualize").unwrap_or(true);
+        let infinite_scroll = LocalStorage::get("infinite_scroll").unwrap_or(false);
+        let watch_interval = LocalStorage::get("watch_interval").unwrap_or(DEFAULT_WATCH_INTERVAL);
+        let watch_notify = LocalStorage::get("watch_notify").unwrap_or(false);
+        let corpus = LocalStorage::get("corpus").unwrap_or(false);
+        let corpus_only = LocalStorage::get("corpus_only").unwrap_or(false);
+
+        Settings {
+            live_api,
+            sensitive_media,
+            virtualize,
+            infinite_scroll,
+            watch_interval,
+            watch_notify,
+            corpus,
+            corpus_only,
+        }
+    }
+
+    pub fn store(&self) {
+        LocalStorage::set("live_api", self.live_api.clone()).unwrap();
+        LocalStorage::set("sensitive_media", self.sensitive_media.to_string()).unwrap();
+        LocalStorage::set("virtualize", self.virtualize).unwrap();
+        LocalStorage::set("infinite_scroll", self.infinite_scroll).unwrap();
+        LocalStorage::set("watch_interval", self.watch_interval).unwrap();
+        LocalStorage::set("watch_notify", self.watch_notify).unwrap();
+        LocalStorage::set("corpus", self.corpus).unwrap();
+        LocalStorage::set("corpus_only", self.corpus_only).unwrap();
+    }
+
+    pub fn live_api(&self) -> &str {
+        if self.live_api.trim().is_empty() {
+            DEFAULT_LIVE_API
+        } else {
+            self.live_api.trim()
+        }
+    }
+
+    pub fn watch_interval_ms(&self) -> u32 {
+        self.watch_interval
+            .clamp(MIN_WATCH_INTERVAL, MAX_WATCH_INTERVAL)
+            .saturating_mul(1000)
+    }
+
+    /// Whether searches run against the local corpus
+    pub fn search_corpus(&self) -> bool {
+        self.corpus && self.corpus_only
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SensitiveMedia {
+    Blur,
+    Hide,
+    Show,
+}
+
+static BLUR_STR: &str = "Blur";
+static HIDE_STR: &str = "Hide";
+static SHOW_STR: &str = "Show";
+
+impl SensitiveMedia {
+    pub fn list() -> Vec<String> {
+        vec![BLUR_STR.into(), HIDE_STR.into(), SHOW_STR.into()]
+    }
+}
+
+impl Display for SensitiveMedia {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Blur => write!(f, "{}", BLUR_STR),
+            Self::Hide => write!(f, "{}", HIDE_STR),
+            Self::Show => write!(f, "{}", SHOW_STR),
+        }
+    }
+}
+
+impl FromStr for SensitiveMedia {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            x if x == BLUR_STR => Ok(Self::Blur),
+            x if x == HIDE_STR => Ok(Self::Hide),
+            x if x == SHOW_STR => Ok(Self::Show),
+            _ => Err(()),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use gloo_worker::{HandlerId, Worker, WorkerScope};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::WorkerGlobalScope;
+
+use crate::dump::{self, DumpFilter, Progress};
+use crate::import;
+use crate::params::Endpoint;
+use crate::pushshift::{Leniency, Page};
+
+/// Script of the worker binary, as output by trunk
+pub static WORKER_PATH: &str = "worker.js";
+
+/// Work for the worker, ids match responses to requests
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ParseRequest {
+    /// Parse an API response
+    Parse {
+        id: u32,
+        endpoint: Endpoint,
+        json: String,
+        tz_offset: i64,
+    },
+    /// Parse an exported results file or stored items
+    Import {
+        id: u32,
+        text: String,
+        /// Only items matching this are kept
+        filter: Option<DumpFilter>,
+        tz_offset: i64,
+    },
+    /// Search a dump file, passed as an object URL
+    ScanDump {
+        id: u32,
+        url: String,
+        filter: DumpFilter,
+        tz_offset: i64,
+    },
+    CancelScan {
+        id: u32,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub enum ParseResponse {
+    Parsed {
+        id: u32,
+        result: Result<Page, String>,
+    },
+    DumpProgress {
+        id: u32,
+        progress: Progress,
+    },
+    /// Whether the scan was stopped early, or why it failed
+    DumpDone {
+        id: u32,
+        result: Result<bool, String>,
+    },
+}
+
+/// Parses and filters results off the UI thread
+pub struct ParseWorker {
+    scans: HashMap<u32, Rc<Cell<bool>>>,
+}
+
+impl Worker for ParseWorker {
+    /// Id of a finished dump scan
+    type Message = u32;
+    type Input = ParseRequest;
+    type Output = ParseResponse;
+
+    fn create(_scope: &WorkerScope<Self>) -> Self {
+        Self {
+            scans: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, _scope: &WorkerScope<Self>, id: u32) {
+        self.scans.remove(&id);
+    }
+
+    fn received(&mut self, scope: &WorkerScope<Self>, msg: ParseRequest, who: HandlerId) {
+        match msg {
+            ParseRequest::Parse {
+                id,
+                endpoint,
+                json,
+                tz_offset,
+            } => {
+                let (result, leniency) = Leniency::track(|| endpoint.parse_page(json, tz_offset));
+                let result = result
+                    .map(|page| Page { leniency, ..page })
+                    .map_err(|e| e.to_string());
+                scope.respond(who, ParseResponse::Parsed { id, result });
+            }
+            ParseRequest::Import {
+                id,
+                text,
+                filter,
+                tz_offset,
+            } => {
+                let (result, leniency) = Leniency::track(|| import::parse(&text, tz_offset));
+                let result = result
+                    .map(|mut items| {
+                        if let Some(filter) = filter {
+                            items.retain(|r| filter.matches(r));
+                        }
+                        items
+                    })
+                    .map(|items| Page {
+                        items,
+                        metadata: None,
+                        leniency,
+                    })
+                    .map_err(|e| e.to_string());
+                scope.respond(who, ParseResponse::Parsed { id, result });
+            }
+            ParseRequest::ScanDump {
+                id,
+                url,
+                filter,
+                tz_offset,
+            } => {
+                let cancel = Rc::new(Cell::new(false));
+                self.scans.insert(id, cancel.clone());
+                let scope = scope.clone();
+                wasm_bindgen_futures::spawn_local(async move {
+                    let on_progress =
+                        |progress| scope.respond(who, ParseResponse::DumpProgress { id, progress });
+                    let result = match fetch_blob(&url).await {
+                        Ok(blob) => dump::scan(blob, filter, tz_offset, cancel, on_progress).await,
+                        Err(e) => Err(e),
+                    };
+                    scope.respond(who, ParseResponse::DumpDone { id, result });
+                    scope.send_message(id);
+                });
+            }
+            ParseRequest::CancelScan { id } => {
+                if let Some(cancel) = self.scans.get(&id) {
+                    cancel.set(true);
+                }
+            }
+        }
+    }
+}
+
+/// Get the file behind an object URL created by the page
+async fn fetch_blob(url: &str) -> Result<gloo_file::Blob, String> {
+    let global: WorkerGlobalScope = js_sys::global().unchecked_into();
+    let response: web_sys::Response = JsFuture::from(global.fetch_with_str(url))
+        .await
+        .map_err(|e| format!("unable to read file: {:?}", e))?
+        .unchecked_into();
+    let blob = response
+        .blob()
+        .map_err(|e| format!("unable to read file: {:?}", e))?;
+    let blob: web_sys::Blob = JsFuture::from(blob)
+        .await
+        .map_err(|e| format!("unable to read file: {:?}", e))?
+        .unchecked_into();
+    Ok(blob.into())
+}
@@ -0,0 +1,136 @@
+//! Samples of the archive formats of different years, all must parse
+
+use pushshift_web::import;
+use pushshift_web::params::Endpoint;
+use pushshift_web::pushshift::{Edited, Leniency, RedditType};
+
+fn fixture(name: &str) -> String {
+    let path = format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+    std::fs::read_to_string(path).unwrap()
+}
+
+fn import(name: &str) -> Vec<RedditType> {
+    import::parse(&fixture(name), 0).unwrap()
+}
+
+/// Items survive being written out and read back, as exports and the corpus do
+fn assert_round_trip(items: &[RedditType]) {
+    let json: Vec<String> = items
+        .iter()
+        .map(|i| serde_json::to_string(i).unwrap())
+        .collect();
+    assert_eq!(import::parse(&json.join("\n"), 0).unwrap(), items);
+}
+
+#[test]
+fn comments_2008() {
+    let items = import("comments_2008.ndjson");
+    assert_eq!(items.len(), 2);
+
+    // String timestamps and no permalink
+    assert_eq!(items[0].fullname(), "t1_c02s9x1");
+    assert_eq!(items[0].time(), 1207008000);
+    assert_eq!(
+        items[0].content(),
+        "Welcome to the new comment system & thanks for testing"
+    );
+    assert_eq!(
+        items[0].permalink(),
+        "https://www.reddit.com/r/reddit.com/comments/6e5x2//c02s9x1?context=10000"
+    );
+    assert_eq!(items[0].edited(), Edited::No);
+
+    // A link id without its prefix
+    assert_eq!(
+        items[1].permalink(),
+        "https://www.reddit.com/r/reddit.com/comments/6e5x2//c02s9y4?context=10000"
+    );
+    assert_round_trip(&items);
+}
+
+#[test]
+fn comments_2012() {
+    let items = import("comments_2012.ndjson");
+    assert_eq!(items.len(), 2);
+
+    // A prefixed id and an edit time
+    assert_eq!(items[0].fullname(), "t1_c4xk2ab");
+    assert_eq!(items[0].author(), "throwaway_42");
+    assert_eq!(items[0].edited(), Edited::At(1341104400));
+
+    // Edits before edit times were kept
+    assert_eq!(items[1].fullname(), "t1_c4xk2ac");
+    assert_eq!(items[1].edited(), Edited::Yes);
+    assert_round_trip(&items);
+}
+
+#[test]
+fn submissions_2016() {
+    let items = import("submissions_2016.ndjson");
+    assert_eq!(items.len(), 2);
+
+    // Float timestamps and a null thumbnail
+    assert_eq!(items[0].fullname(), "t3_4h8x2k");
+    assert_eq!(items[0].time(), 1462060800);
+    assert_eq!(items[0].content(), "http://i.imgur.com/abc123.jpg");
+
+    // No permalink and no url
+    assert_eq!(items[1].time(), 1462064400);
+    assert_eq!(items[1].title(), Some("A question about the 1600s"));
+    assert_eq!(items[1].content(), "Did people \"really\" believe this?");
+    assert_eq!(
+        items[1].permalink(),
+        "https://www.reddit.com/r/AskHistorians/comments/4h8y3m"
+    );
+    assert_eq!(items[1].edited(), Edited::At(1462068000));
+    assert_round_trip(&items);
+}
+
+#[test]
+fn comments_2019_api() {
+    let items = Endpoint::Comment
+        .parse(fixture("comments_2019_api.json"), 0)
+        .unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].fullname(), "t1_et1ab2c");
+    assert_eq!(
+        items[0].permalink(),
+        "https://www.reddit.com/r/AskHistorians/comments/c7ab12/a_question/et1ab2c/?context=10000"
+    );
+    assert_round_trip(&items);
+}
+
+#[test]
+fn submissions_2023() {
+    let items = import("submissions_2023.ndjson");
+    assert_eq!(items.len(), 2);
+
+    // A null over_18
+    assert_eq!(items[0].fullname(), "t3_13x4abc");
+    assert_eq!(items[0].content(), "Ask your questions here");
+    assert_eq!(items[0].edited(), Edited::No);
+
+    assert_eq!(items[1].subreddit(), "rust");
+    assert_eq!(items[1].edited(), Edited::At(1685584800));
+    assert_round_trip(&items);
+}
+
+#[test]
+fn fallbacks_are_counted() {
+    // A null over_18 and an edit time of an unexpected type
+    let text = fixture("submissions_2023.ndjson").replacen(
+        r#""edited":1685584800.0"#,
+        r#""edited":[1685584800]"#,
+        1,
+    );
+    let (items, leniency) = Leniency::track(|| import::parse(&text, 0).unwrap());
+    assert_eq!(items[1].edited(), Edited::No);
+    assert_eq!(
+        leniency,
+        Leniency {
+            defaulted: 1,
+            skipped: 1
+        }
+    );
+    assert_eq!(leniency.warnings().len(), 2);
+}